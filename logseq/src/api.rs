@@ -0,0 +1,380 @@
+use crate::{logseq::LogseqSource, shell_quote};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shellexpand::tilde;
+use std::{
+    fs::{OpenOptions, Permissions},
+    io::{self, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::PathBuf,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] Box<ureq::Error>),
+    #[error("I/O error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Graph {expected} is not open in logseq, {actual} is")]
+    GraphNotOpen { expected: String, actual: String },
+}
+
+/// Logseq desktop's local HTTP API server
+#[derive(Debug, Clone)]
+pub struct LogseqApi {
+    /// e.g. `http://127.0.0.1:12315`
+    pub url: String,
+    pub token: String,
+    /// `Authorization` header read by curl, keeps the token out of the
+    /// commands of the results and of curl's arguments
+    pub header_file: PathBuf,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ApiRequest {
+    pub method: String,
+    pub args: Vec<Value>,
+}
+
+impl ApiRequest {
+    pub fn new(method: &str, args: Vec<Value>) -> Self {
+        ApiRequest {
+            method: method.to_string(),
+            args,
+        }
+    }
+
+    pub fn create_page(title: &str) -> Self {
+        ApiRequest::new(
+            "logseq.Editor.createPage",
+            vec![
+                Value::from(title),
+                Value::Object(Default::default()),
+                serde_json::json!({ "redirect": true }),
+            ],
+        )
+    }
+
+    /// Journal page `title`, logseq treats it as the journal of that day
+    pub fn create_journal(title: &str) -> Self {
+        ApiRequest::new(
+            "logseq.Editor.createPage",
+            vec![
+                Value::from(title),
+                Value::Object(Default::default()),
+                serde_json::json!({ "redirect": true, "journal": true }),
+            ],
+        )
+    }
+
+    pub fn append_block(page: &str, content: &str) -> Self {
+        ApiRequest::new(
+            "logseq.Editor.appendBlockInPage",
+            vec![Value::from(page), Value::from(content)],
+        )
+    }
+
+    pub fn current_graph() -> Self {
+        ApiRequest::new("logseq.App.getCurrentGraph", vec![])
+    }
+
+    pub fn datascript_query(datalog: &str) -> Self {
+        ApiRequest::new("logseq.DB.datascriptQuery", vec![Value::from(datalog)])
+    }
+}
+
+/// Response of `logseq.App.getCurrentGraph`
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CurrentGraph {
+    pub name: String,
+}
+
+impl CurrentGraph {
+    /// Name of the graph as configured, DB graphs are prefixed in some versions
+    pub fn graph_name(&self) -> &str {
+        self.name.trim_start_matches("logseq_db_")
+    }
+}
+
+impl LogseqApi {
+    pub const DEFAULT_URL: &str = "http://127.0.0.1:12315";
+    const HEADER_FILE: &str = "~/.cache/illef-findex-plugin/logseq.api-header";
+
+    pub fn new(url: String, token: String) -> Self {
+        LogseqApi {
+            url,
+            token,
+            header_file: PathBuf::from(&*tilde(Self::HEADER_FILE)),
+        }
+    }
+
+    /// Writes the `Authorization` header used by [`command`](Self::command),
+    /// only readable by the user
+    pub fn write_header_file(&self) -> io::Result<()> {
+        if let Some(parent_dir) = self.header_file.parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&self.header_file)?;
+        // `mode` only applies to new files
+        file.set_permissions(Permissions::from_mode(0o600))?;
+        writeln!(file, "Authorization: Bearer {}", self.token)
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/api", self.url.trim_end_matches('/'))
+    }
+
+    pub fn call(&self, request: &ApiRequest) -> Result<Value, ApiError> {
+        let response = ureq::post(&self.endpoint())
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .send_json(request)
+            .map_err(Box::new)?;
+        Ok(response.into_json()?)
+    }
+
+    /// Graph open in the desktop app, `None` when no graph is open
+    pub fn current_graph(&self) -> Result<Option<CurrentGraph>, ApiError> {
+        let value = self.call(&ApiRequest::current_graph())?;
+        Ok(serde_json::from_value(value).ok())
+    }
+
+    /// Shell command sending `request` with curl, results only run a command
+    /// once selected
+    pub fn command(&self, request: &ApiRequest) -> String {
+        format!(
+            "curl -sf -X POST {} -H {} -H {} -d {}",
+            shell_quote(&self.endpoint()),
+            shell_quote(&format!("@{}", self.header_file.to_string_lossy())),
+            shell_quote("Content-Type: application/json"),
+            shell_quote(&serde_json::to_string(request).unwrap_or_default())
+        )
+    }
+}
+
+/// Queries a graph through the HTTP API, which only sees the graph open in
+/// the desktop app
+pub struct ApiSource {
+    pub graph: String,
+    pub api: LogseqApi,
+}
+
+impl LogseqSource for ApiSource {
+    fn graph(&self) -> &str {
+        &self.graph
+    }
+
+    fn is_open(&self) -> Result<bool, String> {
+        let current = self.api.current_graph().map_err(|e| e.to_string())?;
+        Ok(current.is_some_and(|g| g.graph_name() == self.graph))
+    }
+
+    fn query(&self, datalog: &str) -> Result<Value, String> {
+        // another graph may have been opened since the sync started
+        let current = self.api.current_graph().map_err(|e| e.to_string())?;
+        if current.as_ref().map(|g| g.graph_name()) != Some(self.graph.as_str()) {
+            return Err(ApiError::GraphNotOpen {
+                expected: self.graph.clone(),
+                actual: current.map(|g| g.name).unwrap_or_default(),
+            }
+            .to_string());
+        }
+
+        self.api
+            .call(&ApiRequest::datascript_query(datalog))
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq;
+    use serde_json::json;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        process::Command,
+        sync::mpsc,
+        thread,
+    };
+
+    /// Request received by the mock server
+    struct Received {
+        headers: Vec<String>,
+        body: Value,
+    }
+
+    /// HTTP API answering each request with `respond(body)`
+    fn mock_server(respond: fn(&Value) -> Value) -> (String, mpsc::Receiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                // clients may send several requests over one connection
+                loop {
+                    let mut headers = vec![];
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            break;
+                        }
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        headers.push(line.trim().to_string());
+                    }
+                    if headers.is_empty() {
+                        break;
+                    }
+                    let length = headers
+                        .iter()
+                        .find_map(|h| {
+                            h.to_lowercase()
+                                .strip_prefix("content-length: ")?
+                                .parse()
+                                .ok()
+                        })
+                        .unwrap_or(0);
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    let body: Value = serde_json::from_slice(&body).unwrap();
+
+                    let response = respond(&body).to_string();
+                    reader
+                        .get_mut()
+                        .write_all(
+                            format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                                response.len(),
+                                response
+                            )
+                            .as_bytes(),
+                        )
+                        .unwrap();
+                    let _ = tx.send(Received { headers, body });
+                }
+            }
+        });
+        (url, rx)
+    }
+
+    /// Answers queries by their datalog, like `FixtureSource` in the cli tests
+    fn graph_api(request: &Value) -> Value {
+        let datalog = request["args"][0].as_str().unwrap_or_default();
+        match request["method"].as_str().unwrap_or_default() {
+            "logseq.App.getCurrentGraph" => json!({"name": "illef", "path": "/home/illef/logseq"}),
+            "logseq.DB.datascriptQuery" if datalog.contains(":logseq.class/Tag") => json!([
+                [{"id": 387, "title": "Book", "icon": {"type": "emoji", "id": "books"}}],
+            ]),
+            "logseq.DB.datascriptQuery" if datalog.contains(":block/alias") => json!([
+                [{"uuid": "6821bf0d-3ac7-42cc-a5ba-5b742b809633", "title": "AI", "tags": []}],
+                [{"uuid": "67c56741-df35-408b-a4d8-df08ebcd030e", "title": "삶", "updatedAt": 1746976525000_i64, "tags": [{"id": 387}]}],
+            ]),
+            "logseq.DB.datascriptQuery" => json!([]),
+            _ => Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_command_against_mock() {
+        let (url, received) = mock_server(|_| json!({}));
+        let dir = tempfile::tempdir().unwrap();
+        let api = LogseqApi {
+            header_file: dir.path().join("logseq.api-header"),
+            ..LogseqApi::new(format!("{}/", url), "secret".to_string())
+        };
+        api.write_header_file().unwrap();
+        let request = ApiRequest::append_block("Oct 19th, 2026", "it's #inbox");
+        let command = api.command(&request);
+
+        let status = Command::new("bash")
+            .arg("-c")
+            .arg(&command)
+            .status()
+            .unwrap();
+        let received = received.recv().unwrap();
+
+        assert!(status.success());
+        assert!(!command.contains("secret"));
+        assert_eq!(
+            std::fs::metadata(&api.header_file)
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o600
+        );
+        assert_eq!(received.headers[0], "POST /api HTTP/1.1");
+        assert!(received
+            .headers
+            .contains(&"Authorization: Bearer secret".to_string()));
+        assert_eq!(
+            received.body,
+            json!({
+                "method": "logseq.Editor.appendBlockInPage",
+                "args": ["Oct 19th, 2026", "it's #inbox"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_api_source() {
+        let (url, received) = mock_server(graph_api);
+        let source = ApiSource {
+            graph: "illef".to_string(),
+            api: LogseqApi::new(url, "secret".to_string()),
+        };
+
+        let blocks = source.query("[:find (pull ?b [*])]").unwrap();
+        let tags = logseq::get_logseq_tags(&source).unwrap();
+        let pages = logseq::get_logseq_pages(&source, &tags, &Default::default(), None).unwrap();
+
+        assert_eq!(blocks, json!([]));
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].title, "AI");
+        assert_eq!(pages[1].graph, "illef");
+        assert_eq!(pages[1].title, "삶");
+        assert_eq!(pages[1].updated_at, Some(1746976525000));
+        assert_eq!(pages[1].tags[0].name, "Book");
+        assert_eq!(pages[1].tags[0].icon.as_ref().unwrap().id, "books");
+
+        let requests: Vec<Value> = received.try_iter().map(|r| r.body).collect();
+        assert_eq!(
+            requests[1],
+            json!({"method": "logseq.DB.datascriptQuery", "args": ["[:find (pull ?b [*])]"]})
+        );
+    }
+
+    #[test]
+    fn test_graph_name() {
+        let graph = |name: &str| CurrentGraph {
+            name: name.to_string(),
+        };
+        assert_eq!(graph("logseq_db_illef").graph_name(), "illef");
+        assert_eq!(graph("work").graph_name(), "work");
+    }
+
+    #[test]
+    fn test_api_source_other_graph_open() {
+        let (url, _received) = mock_server(graph_api);
+        let source = ApiSource {
+            graph: "work".to_string(),
+            api: LogseqApi::new(url, "secret".to_string()),
+        };
+
+        assert_eq!(source.is_open(), Ok(false));
+        assert_eq!(
+            source.query("[:find ?b]").unwrap_err(),
+            "Graph work is not open in logseq, illef is"
+        );
+    }
+}
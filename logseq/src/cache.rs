@@ -1,7 +1,9 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
+use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use shellexpand::tilde;
 use thiserror::Error;
 use ureq::serde_json;
@@ -20,11 +22,54 @@ pub struct LogseqTag {
     pub icon: Option<LogseqIcon>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
 pub struct LogseqPage {
+    /// Graph the page belongs to
+    #[serde(default)]
+    pub graph: String,
     pub title: String,
     pub uuid: String,
     pub tags: Vec<LogseqTag>,
+    /// Other names of the page, from the `alias` property
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Values of the configured properties, by lowercased property name
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    pub updated_at: Option<i64>,
+    /// Day of a journal page as `yyyymmdd`, `None` for other pages
+    #[serde(default)]
+    pub journal_day: Option<u32>,
+}
+
+/// A block inside a page
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LogseqBlockEntry {
+    pub graph: String,
+    pub content: String,
+    pub uuid: String,
+    /// Title of the page the block belongs to
+    pub page: String,
+    pub tags: Vec<LogseqTag>,
+    pub updated_at: Option<i64>,
+}
+
+/// A block tagged as task, statuses and priorities are lowercased
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LogseqTaskEntry {
+    pub graph: String,
+    pub content: String,
+    /// Empty for tasks of file based graphs without an `id::` property
+    pub uuid: String,
+    /// Title of the page the task belongs to
+    pub page: String,
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    /// Milliseconds since epoch
+    pub scheduled: Option<i64>,
+    /// Milliseconds since epoch
+    pub deadline: Option<i64>,
+    pub tags: Vec<LogseqTag>,
     pub updated_at: Option<i64>,
 }
 
@@ -36,37 +81,210 @@ pub enum CacheError {
     IoError(#[from] io::Error),
 }
 
-pub struct FilePageCache {
+/// Entries cached per graph, synced blocks are identified by their uuid
+pub trait CacheEntry: Serialize + DeserializeOwned {
+    fn uuid(&self) -> &str;
+}
+
+impl CacheEntry for LogseqPage {
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+}
+
+impl CacheEntry for LogseqBlockEntry {
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+}
+
+impl CacheEntry for LogseqTaskEntry {
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+}
+
+/// Applies the entries updated since the last sync to the cached ones.
+/// `existing` are the uuids of all entries in the graph when they were
+/// checked, cached entries not among them were deleted.
+pub fn merge_changes<T: CacheEntry>(
+    cached: Vec<T>,
+    updated: Vec<T>,
+    existing: Option<&HashSet<String>>,
+) -> Vec<T> {
+    let cached_uuids: HashSet<String> = cached.iter().map(|e| e.uuid().to_string()).collect();
+    let (replacing, added): (Vec<T>, Vec<T>) = updated
+        .into_iter()
+        .partition(|entry| cached_uuids.contains(entry.uuid()));
+    let mut replacing: HashMap<String, T> = replacing
+        .into_iter()
+        .map(|entry| (entry.uuid().to_string(), entry))
+        .collect();
+
+    cached
+        .into_iter()
+        .filter(|entry| existing.is_none_or(|uuids| uuids.contains(entry.uuid())))
+        .map(|entry| replacing.remove(entry.uuid()).unwrap_or(entry))
+        .chain(added)
+        .collect()
+}
+
+/// JSON file holding the entries of one graph
+pub struct FileCache<T> {
     file_name: PathBuf,
+    entries: PhantomData<T>,
 }
 
+pub type FilePageCache = FileCache<LogseqPage>;
+pub type FileBlockCache = FileCache<LogseqBlockEntry>;
+pub type FileTaskCache = FileCache<LogseqTaskEntry>;
+
 impl FilePageCache {
-    pub fn default() -> FilePageCache {
-        return FilePageCache::new(&*tilde(&format!(
-            "~/.cache/illef-findex-plugin/logseq.pages.cache.json",
-        )));
+    /// Cache of the pages of one graph
+    pub fn for_graph(graph: &str) -> FilePageCache {
+        FileCache::in_cache_dir(graph, "pages")
+    }
+}
+
+impl FileBlockCache {
+    /// Cache of the blocks of one graph
+    pub fn for_graph(graph: &str) -> FileBlockCache {
+        FileCache::in_cache_dir(graph, "blocks")
+    }
+}
+
+impl FileTaskCache {
+    /// Cache of the tasks of one graph
+    pub fn for_graph(graph: &str) -> FileTaskCache {
+        FileCache::in_cache_dir(graph, "tasks")
+    }
+}
+
+/// Cache of the single graph synced before graphs were configurable
+const LEGACY_PAGE_CACHE: &str = "~/.cache/illef-findex-plugin/logseq.pages.cache.json";
+
+/// `~/.cache/illef-findex-plugin/logseq.<graph>.<suffix>`, path separators and
+/// `%` in the graph name are percent encoded so it stays in the cache dir
+pub fn graph_file(graph: &str, suffix: &str) -> PathBuf {
+    let graph: String = graph
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | '%' | '\0' => format!("%{:02X}", c as u32),
+            c => c.to_string(),
+        })
+        .collect();
+    PathBuf::from(&*tilde(&format!(
+        "~/.cache/illef-findex-plugin/logseq.{}.{}",
+        graph, suffix
+    )))
+}
+
+/// Removes the page cache written before caches were kept per graph, the
+/// graphs' own caches replace it on their first sync
+pub fn remove_legacy_cache() {
+    let file_name = tilde(LEGACY_PAGE_CACHE);
+    match std::fs::remove_file(&*file_name) {
+        Ok(()) => log::info!("removed legacy cache file={:?}", file_name),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => log::warn!(
+            "legacy cache removal failed file={:?} error={:?}",
+            file_name,
+            e.to_string()
+        ),
+    }
+}
+
+impl<T: CacheEntry> FileCache<T> {
+    fn in_cache_dir(graph: &str, kind: &str) -> Self {
+        FileCache::new(graph_file(graph, &format!("{}.cache.json", kind)))
     }
 
     pub fn new<P: AsRef<Path>>(file_name: P) -> Self {
-        FilePageCache {
+        FileCache {
             file_name: file_name.as_ref().to_path_buf(),
+            entries: PhantomData,
         }
     }
 
-    pub fn update_cache(&self, pages: Vec<LogseqPage>) -> Result<(), CacheError> {
+    /// Writes `entries` unless the cache already holds them, returns whether
+    /// the file was written
+    pub fn update_cache(&self, entries: &[T]) -> Result<bool, CacheError> {
+        let json_str = serde_json::to_string(entries)?;
+        if std::fs::read_to_string(&self.file_name).is_ok_and(|cached| cached == json_str) {
+            return Ok(false);
+        }
+
         if let Some(parent_dir) = self.file_name.parent() {
             std::fs::create_dir_all(parent_dir)?;
         }
-
-        let json_str = serde_json::to_string(&pages)?;
         std::fs::write(&self.file_name, json_str)?;
 
-        Ok(())
+        Ok(true)
     }
 
-    pub fn load_cache(&self) -> Result<Vec<LogseqPage>, CacheError> {
+    pub fn load_cache(&self) -> Result<Vec<T>, CacheError> {
         let file_contents = std::fs::read_to_string(&self.file_name)?;
-        let pages: Vec<LogseqPage> = serde_json::from_str(&file_contents)?;
-        Ok(pages)
+        let entries: Vec<T> = serde_json::from_str(&file_contents)?;
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(uuid: &str, title: &str) -> LogseqPage {
+        LogseqPage {
+            graph: "illef".to_string(),
+            title: title.to_string(),
+            uuid: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn titles(pages: &[LogseqPage]) -> Vec<&str> {
+        pages.iter().map(|p| p.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_merge_changes() {
+        let cached = vec![page("a", "Rust"), page("b", "Go"), page("c", "Zig")];
+        let updated = vec![page("d", "Nim"), page("b", "Golang")];
+
+        let merged = merge_changes(cached.clone(), updated.clone(), None);
+        assert_eq!(titles(&merged), vec!["Rust", "Golang", "Zig", "Nim"]);
+
+        let existing = HashSet::from(["a".to_string(), "b".to_string(), "d".to_string()]);
+        let merged = merge_changes(cached, updated, Some(&existing));
+        assert_eq!(titles(&merged), vec!["Rust", "Golang", "Nim"]);
+    }
+
+    #[test]
+    fn test_graph_file() {
+        let cache_dir = PathBuf::from(&*tilde("~/.cache/illef-findex-plugin"));
+        assert_eq!(
+            graph_file("illef", "pages.cache.json"),
+            cache_dir.join("logseq.illef.pages.cache.json")
+        );
+        assert_eq!(
+            graph_file("../work/notes", "status.json"),
+            cache_dir.join("logseq...%2Fwork%2Fnotes.status.json")
+        );
+        assert_eq!(
+            graph_file("50%", "status.json").parent(),
+            Some(cache_dir.as_path())
+        );
+    }
+
+    #[test]
+    fn test_skip_unchanged_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FilePageCache::new(dir.path().join("logseq.illef.pages.cache.json"));
+        let pages = vec![page("a", "Rust")];
+
+        assert!(cache.update_cache(&pages).unwrap());
+        assert!(!cache.update_cache(&pages).unwrap());
+        assert!(cache.update_cache(&[page("a", "Rust 2024")]).unwrap());
+        assert_eq!(titles(&cache.load_cache().unwrap()), vec!["Rust 2024"]);
     }
 }
@@ -0,0 +1,341 @@
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Invalid EDN at {position}: {message}")]
+pub struct EdnError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// Value of the subset of EDN the logseq CLI prints
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edn {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Char(char),
+    Keyword(String),
+    Symbol(String),
+    List(Vec<Edn>),
+    Vector(Vec<Edn>),
+    Set(Vec<Edn>),
+    Map(Vec<(Edn, Edn)>),
+    Uuid(String),
+    Inst(String),
+    Tagged(String, Box<Edn>),
+}
+
+impl Edn {
+    /// Converts to JSON the way `jet --to json` does, keywords lose their
+    /// leading `:` and uuids and instants become strings.
+    pub fn into_json(self) -> Value {
+        match self {
+            Edn::Nil => Value::Null,
+            Edn::Bool(b) => Value::Bool(b),
+            Edn::Int(i) => Value::from(i),
+            Edn::Float(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+            Edn::String(s) | Edn::Keyword(s) | Edn::Symbol(s) | Edn::Uuid(s) | Edn::Inst(s) => {
+                Value::String(s)
+            }
+            Edn::Char(c) => Value::String(c.to_string()),
+            Edn::List(items) | Edn::Vector(items) | Edn::Set(items) => {
+                Value::Array(items.into_iter().map(Edn::into_json).collect())
+            }
+            Edn::Map(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let key = match k.into_json() {
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        };
+                        (key, v.into_json())
+                    })
+                    .collect::<Map<_, _>>(),
+            ),
+            Edn::Tagged(_, value) => value.into_json(),
+        }
+    }
+}
+
+/// Parses a single EDN value, surrounding whitespace and comments are allowed
+pub fn parse(input: &str) -> Result<Edn, EdnError> {
+    let mut parser = Parser { input, position: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace()?;
+    if parser.position < input.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | '(' | ')' | '[' | ']' | '{' | '}' | '"' | ';')
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> EdnError {
+        EdnError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    /// Skips whitespace, commas, comments and `#_` discarded values
+    fn skip_whitespace(&mut self) -> Result<(), EdnError> {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == ',' {
+                self.next();
+            } else if c == ';' {
+                while self.next().is_some_and(|c| c != '\n') {}
+            } else if self.input[self.position..].starts_with("#_") {
+                self.position += 2;
+                self.value()?;
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Characters up to the next delimiter
+    fn token(&mut self) -> &str {
+        let start = self.position;
+        while self.peek().is_some_and(|c| !is_delimiter(c)) {
+            self.next();
+        }
+        &self.input[start..self.position]
+    }
+
+    fn value(&mut self) -> Result<Edn, EdnError> {
+        self.skip_whitespace()?;
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some('"') => self.string().map(Edn::String),
+            Some('(') => self.sequence(')').map(Edn::List),
+            Some('[') => self.sequence(']').map(Edn::Vector),
+            Some('{') => self.map(),
+            Some('#') => self.dispatch(),
+            Some(':') => {
+                self.next();
+                match self.token() {
+                    "" => Err(self.error("empty keyword")),
+                    name => Ok(Edn::Keyword(name.to_string())),
+                }
+            }
+            Some('\\') => self.char(),
+            Some(')' | ']' | '}') => Err(self.error("unexpected delimiter")),
+            Some(_) => self.atom(),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, EdnError> {
+        self.next();
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(text),
+                Some('\\') => match self.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('"') => text.push('"'),
+                    Some('\\') => text.push('\\'),
+                    Some('u') => {
+                        let end = self.position + 4;
+                        let c = self
+                            .input
+                            .get(self.position..end)
+                            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid unicode escape"))?;
+                        self.position = end;
+                        text.push(c);
+                    }
+                    _ => return Err(self.error("invalid escape")),
+                },
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn sequence(&mut self, close: char) -> Result<Vec<Edn>, EdnError> {
+        self.next();
+        let mut items = vec![];
+        loop {
+            self.skip_whitespace()?;
+            match self.peek() {
+                None => return Err(self.error("unterminated collection")),
+                Some(c) if c == close => {
+                    self.next();
+                    return Ok(items);
+                }
+                Some(_) => items.push(self.value()?),
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Edn, EdnError> {
+        let items = self.sequence('}')?;
+        if items.len() % 2 != 0 {
+            return Err(self.error("map with an odd number of forms"));
+        }
+        let mut items = items.into_iter();
+        let mut entries = vec![];
+        while let (Some(k), Some(v)) = (items.next(), items.next()) {
+            entries.push((k, v));
+        }
+        Ok(Edn::Map(entries))
+    }
+
+    fn dispatch(&mut self) -> Result<Edn, EdnError> {
+        self.next();
+        if self.peek() == Some('{') {
+            return self.sequence('}').map(Edn::Set);
+        }
+
+        let tag = self.token().to_string();
+        if tag.is_empty() {
+            return Err(self.error("invalid dispatch"));
+        }
+        match (tag.as_str(), self.value()?) {
+            ("uuid", Edn::String(s)) => Ok(Edn::Uuid(s)),
+            ("inst", Edn::String(s)) => Ok(Edn::Inst(s)),
+            ("uuid" | "inst", _) => Err(self.error("expected a string")),
+            (_, value) => Ok(Edn::Tagged(tag, Box::new(value))),
+        }
+    }
+
+    fn char(&mut self) -> Result<Edn, EdnError> {
+        self.next();
+        let start = self.position;
+        // the first character may be a delimiter, e.g. `\(`
+        self.next();
+        while self.peek().is_some_and(|c| !is_delimiter(c)) {
+            self.next();
+        }
+        let c = match &self.input[start..self.position] {
+            "newline" => '\n',
+            "space" => ' ',
+            "tab" => '\t',
+            "return" => '\r',
+            name if name.chars().count() == 1 => name.chars().next().unwrap(),
+            _ => return Err(self.error("invalid character")),
+        };
+        Ok(Edn::Char(c))
+    }
+
+    fn atom(&mut self) -> Result<Edn, EdnError> {
+        let token = self.token();
+        let value = match token {
+            "nil" => Edn::Nil,
+            "true" => Edn::Bool(true),
+            "false" => Edn::Bool(false),
+            _ if token.starts_with(|c: char| c.is_ascii_digit())
+                || (token.len() > 1
+                    && token.starts_with(['-', '+'])
+                    && token[1..].starts_with(|c: char| c.is_ascii_digit())) =>
+            {
+                let number = token.trim_end_matches(['N', 'M']);
+                number
+                    .parse::<i64>()
+                    .map(Edn::Int)
+                    .or_else(|_| number.parse::<f64>().map(Edn::Float))
+                    .map_err(|_| self.error("invalid number"))?
+            }
+            _ => Edn::Symbol(token.to_string()),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_scalars() {
+        assert_eq!(parse("nil"), Ok(Edn::Nil));
+        assert_eq!(parse(" true "), Ok(Edn::Bool(true)));
+        assert_eq!(parse("-42"), Ok(Edn::Int(-42)));
+        assert_eq!(parse("1.5"), Ok(Edn::Float(1.5)));
+        assert_eq!(parse("7N"), Ok(Edn::Int(7)));
+        assert_eq!(
+            parse(r#""a \"b\"\né""#),
+            Ok(Edn::String("a \"b\"\né".to_string()))
+        );
+        assert_eq!(
+            parse(":block/title"),
+            Ok(Edn::Keyword("block/title".to_string()))
+        );
+        assert_eq!(parse("\\newline"), Ok(Edn::Char('\n')));
+        assert_eq!(parse("->foo"), Ok(Edn::Symbol("->foo".to_string())));
+    }
+
+    #[test]
+    fn test_parse_collections() {
+        assert_eq!(
+            parse("[1, (2 3) #{:a} {:b nil}] ; comment"),
+            Ok(Edn::Vector(vec![
+                Edn::Int(1),
+                Edn::List(vec![Edn::Int(2), Edn::Int(3)]),
+                Edn::Set(vec![Edn::Keyword("a".to_string())]),
+                Edn::Map(vec![(Edn::Keyword("b".to_string()), Edn::Nil)]),
+            ]))
+        );
+        assert_eq!(
+            parse("[#_ {:skipped 1} #inst \"2024-01-03T10:00:00.000-00:00\" #custom/tag 1]"),
+            Ok(Edn::Vector(vec![
+                Edn::Inst("2024-01-03T10:00:00.000-00:00".to_string()),
+                Edn::Tagged("custom/tag".to_string(), Box::new(Edn::Int(1))),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("[1 2").is_err());
+        assert!(parse("{:a}").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("#uuid 1").is_err());
+        assert!(parse("[#_ ]").is_err());
+        assert_eq!(parse("1 ]").unwrap_err().position, 2);
+    }
+
+    #[test]
+    fn test_into_json() {
+        let edn = parse(
+            r#"{:block/title "삶", :block/uuid #uuid "6821bf0d-3ac7-42cc-a5ba-5b742b809633", :block/tags [{:db/id 136}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            edn.into_json(),
+            json!({
+                "block/title": "삶",
+                "block/uuid": "6821bf0d-3ac7-42cc-a5ba-5b742b809633",
+                "block/tags": [{"db/id": 136}],
+            })
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use crate::{
+    cache::{LogseqPage, LogseqTag, LogseqTaskEntry},
+    journal,
+};
+use chrono::{Local, NaiveDate};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Directories of a file based graph that contain pages
+pub const PAGE_DIRS: [&str; 2] = ["pages", "journals"];
+
+/// Markers starting a task block, `LATER` and `NOW` belong to the NOW workflow
+const TASK_MARKERS: [&str; 8] = [
+    "TODO",
+    "LATER",
+    "NOW",
+    "DOING",
+    "WAITING",
+    "DONE",
+    "CANCELED",
+    "CANCELLED",
+];
+
+/// Page properties stored in their own `LogseqPage` fields
+const BUILTIN_PROPERTIES: [&str; 4] = ["title", "tags", "alias", "id"];
+
+pub fn is_page_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e == "md" || e == "markdown" || e == "org")
+}
+
+/// Pages of a file based (markdown/org) graph stored in `dir`, with the content of their file
+pub fn read_file_graph(graph: &str, dir: &Path) -> Vec<(LogseqPage, String)> {
+    PAGE_DIRS
+        .iter()
+        .flat_map(|d| page_files(&dir.join(d)))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let updated_at = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64);
+
+            let mut page = parse_page(graph, &path, &content);
+            page.updated_at = updated_at;
+            Some((page, content))
+        })
+        .collect()
+}
+
+/// Task blocks in pages read by `read_file_graph`
+pub fn file_graph_tasks(graph: &str, pages: &[(LogseqPage, String)]) -> Vec<LogseqTaskEntry> {
+    pages
+        .iter()
+        .flat_map(|(page, content)| {
+            let mut tasks = parse_tasks(graph, &page.title, content);
+            for task in &mut tasks {
+                task.updated_at = page.updated_at;
+            }
+            tasks
+        })
+        .collect()
+}
+
+fn page_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .flat_map(|path| {
+            if path.is_dir() {
+                page_files(&path)
+            } else if is_page_file(&path) {
+                vec![path]
+            } else {
+                vec![]
+            }
+        })
+        .collect()
+}
+
+/// Parses a page file, the title comes from the `title` property or the file name
+pub fn parse_page(graph: &str, path: &Path, content: &str) -> LogseqPage {
+    let properties = page_properties(content);
+    let title = properties
+        .get("title")
+        .cloned()
+        .unwrap_or_else(|| title_from_file_name(path));
+
+    let mut tags: Vec<String> = properties
+        .get("tags")
+        .map(|t| property_values(t))
+        .unwrap_or_default();
+    for tag in inline_tags(content) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            tags.push(tag);
+        }
+    }
+
+    LogseqPage {
+        graph: graph.to_string(),
+        title,
+        uuid: properties.get("id").cloned().unwrap_or_default(),
+        tags: tags
+            .into_iter()
+            .map(|name| LogseqTag { name, icon: None })
+            .collect(),
+        aliases: properties
+            .get("alias")
+            .map(|a| property_values(a))
+            .unwrap_or_default(),
+        properties: properties
+            .iter()
+            .filter(|(key, _)| !BUILTIN_PROPERTIES.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), property_values(value).join(", ")))
+            .collect(),
+        updated_at: None,
+        journal_day: journal_day(path),
+    }
+}
+
+/// `- TODO [#A] content` (markdown) or `* TODO content` (org) blocks of a page,
+/// followed by their `SCHEDULED:`, `DEADLINE:` and `id::` lines
+pub fn parse_tasks(graph: &str, page: &str, content: &str) -> Vec<LogseqTaskEntry> {
+    let mut tasks: Vec<LogseqTaskEntry> = vec![];
+    // whether the lines belong to the last task
+    let mut in_task = false;
+
+    for line in content.lines() {
+        let line = line.trim_start();
+        let block = line.strip_prefix("- ").or_else(|| {
+            line.starts_with('*')
+                .then(|| line.trim_start_matches('*').strip_prefix(' '))
+                .flatten()
+        });
+        if let Some(block) = block {
+            in_task = false;
+            let Some((marker, rest)) = block.split_once(' ') else {
+                continue;
+            };
+            if !TASK_MARKERS.contains(&marker) {
+                continue;
+            }
+            let (priority, rest) = match rest.strip_prefix("[#").and_then(|r| r.split_once("] ")) {
+                Some((priority, rest)) => (task_priority(priority), rest),
+                None => (None, rest),
+            };
+
+            in_task = true;
+            tasks.push(LogseqTaskEntry {
+                graph: graph.to_string(),
+                content: rest.trim().to_string(),
+                uuid: String::new(),
+                page: page.to_string(),
+                status: Some(marker.replace("CANCELLED", "CANCELED").to_lowercase()),
+                priority,
+                scheduled: None,
+                deadline: None,
+                tags: inline_tags(rest)
+                    .into_iter()
+                    .map(|name| LogseqTag { name, icon: None })
+                    .collect(),
+                updated_at: None,
+            });
+            continue;
+        }
+
+        let Some(task) = tasks.last_mut().filter(|_| in_task) else {
+            continue;
+        };
+        if let Some(date) = line.strip_prefix("SCHEDULED: ") {
+            task.scheduled = timestamp_millis(date);
+        } else if let Some(date) = line.strip_prefix("DEADLINE: ") {
+            task.deadline = timestamp_millis(date);
+        } else if let Some(id) = line.strip_prefix("id:: ") {
+            task.uuid = id.trim().to_string();
+        }
+    }
+    tasks
+}
+
+/// `A`, `B` and `C` priorities of file graphs, named like the ones of DB graphs
+fn task_priority(priority: &str) -> Option<String> {
+    match priority {
+        "A" => Some("high".to_string()),
+        "B" => Some("medium".to_string()),
+        "C" => Some("low".to_string()),
+        _ => None,
+    }
+}
+
+/// Start of the day of a `<2024-01-03 Wed 10:00>` timestamp, in milliseconds
+fn timestamp_millis(timestamp: &str) -> Option<i64> {
+    let date = timestamp.trim().strip_prefix('<')?.get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|t| t.timestamp_millis())
+}
+
+/// `key:: value` (markdown) or `#+key: value` (org) lines at the top of the page
+fn page_properties(content: &str) -> HashMap<String, String> {
+    let mut properties = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        // the properties may be written as the first block
+        let line = match line.strip_prefix("- ") {
+            Some(rest) if i == 0 => rest.trim(),
+            _ => line,
+        };
+
+        let property = line.split_once(":: ").or_else(|| {
+            line.strip_prefix("#+")
+                .and_then(|l| l.split_once(": "))
+                .or_else(|| line.strip_suffix("::").map(|key| (key, "")))
+        });
+        match property {
+            Some((key, value)) if !key.is_empty() && !key.contains(char::is_whitespace) => {
+                properties.insert(key.to_lowercase(), value.trim().to_string());
+            }
+            _ => break,
+        }
+    }
+    properties
+}
+
+/// Values of a comma separated property, `[[x]]` and `#x` become `x`
+fn property_values(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| {
+            let v = v.trim();
+            let v = v.strip_prefix('#').unwrap_or(v);
+            v.strip_prefix("[[")
+                .and_then(|v| v.strip_suffix("]]"))
+                .unwrap_or(v)
+                .to_string()
+        })
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// `#tag` and `#[[multi word tag]]` references in the page body
+fn inline_tags(content: &str) -> Vec<String> {
+    let mut tags = vec![];
+    let mut rest = content;
+    while let Some(i) = rest.find('#') {
+        let preceded_by_space = rest[..i]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace);
+        rest = &rest[i + 1..];
+        if !preceded_by_space {
+            continue;
+        }
+
+        let tag = if let Some(inner) = rest.strip_prefix("[[") {
+            inner.find("]]").map(|end| &inner[..end])
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || matches!(c, ',' | '.' | '!' | '?' | '"'))
+                .unwrap_or(rest.len());
+            Some(&rest[..end])
+        };
+        // `# heading` and `#+key:` are not tags
+        if let Some(tag) = tag.filter(|t| !t.is_empty() && !t.starts_with(['#', '+'])) {
+            if !tags.iter().any(|t: &String| t.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+    tags
+}
+
+fn is_journal(path: &Path) -> bool {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .is_some_and(|p| p == "journals")
+}
+
+/// Day of a journal file as `yyyymmdd`, journals are named `2024_01_03.md`
+fn journal_day(path: &Path) -> Option<u32> {
+    if !is_journal(path) {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    NaiveDate::parse_from_str(stem, "%Y_%m_%d")
+        .ok()
+        .map(journal::journal_day)
+}
+
+/// `a___b%3F.md` -> `a/b?`, journals `2024_01_03.md` -> `Jan 3rd, 2024`, the
+/// page name logseq gives journals with the default `:journal/page-title-format`
+fn title_from_file_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if is_journal(path) {
+        NaiveDate::parse_from_str(&stem, "%Y_%m_%d")
+            .map(journal::journal_title)
+            .unwrap_or_else(|_| stem.replace('_', "-"))
+    } else {
+        percent_decode(&stem.replace("___", "/"))
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                decoded.push(b);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_graph() -> PathBuf {
+        PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/file-graph"
+        ))
+    }
+
+    fn tag_names(page: &LogseqPage) -> Vec<&str> {
+        page.tags.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_markdown_page() {
+        let content = "title:: Rust/Async\ntags:: [[programming]], #rust\nalias:: async rust, tokio\nstatus:: [[Doing]]\n\n- Futures are #lazy, see #[[The Book]]\n  ## Heading\n- issue#12 is not a tag\n";
+        let page = parse_page("notes", Path::new("pages/rust___async.md"), content);
+
+        assert_eq!(page.graph, "notes");
+        assert_eq!(page.title, "Rust/Async");
+        assert_eq!(
+            tag_names(&page),
+            vec!["programming", "rust", "lazy", "The Book"]
+        );
+        assert_eq!(page.aliases, vec!["async rust", "tokio"]);
+        assert_eq!(
+            page.properties.into_iter().collect::<Vec<_>>(),
+            vec![("status".to_string(), "Doing".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_tasks() {
+        let content = "- TODO [#A] Write the #plugin README\n  SCHEDULED: <2024-01-04 Thu>\n  DEADLINE: <2024-01-05 Fri 10:00>\n  id:: 65a1b2c3-0000-4000-8000-000000000002\n  - TODO sub task\n- not a task\n  DEADLINE: <2024-01-06 Sat>\n- DONE Read a book\n";
+        let tasks = parse_tasks("notes", "Logseq", content);
+
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].content, "Write the #plugin README");
+        assert_eq!(tasks[0].status.as_deref(), Some("todo"));
+        assert_eq!(tasks[0].priority.as_deref(), Some("high"));
+        assert_eq!(tasks[0].tags[0].name, "plugin");
+        assert_eq!(tasks[0].uuid, "65a1b2c3-0000-4000-8000-000000000002");
+        assert_eq!(
+            tasks[0].deadline.unwrap() - tasks[0].scheduled.unwrap(),
+            24 * 60 * 60 * 1000
+        );
+        assert_eq!(tasks[1].content, "sub task");
+        assert_eq!(tasks[1].deadline, None);
+        assert_eq!(tasks[2].status.as_deref(), Some("done"));
+
+        let org = parse_tasks(
+            "notes",
+            "삶",
+            "* What matters\n** CANCELLED [#C] Read Camus\n",
+        );
+        assert_eq!(org[0].status.as_deref(), Some("canceled"));
+        assert_eq!(org[0].priority.as_deref(), Some("low"));
+    }
+
+    #[test]
+    fn test_title_from_file_name() {
+        assert_eq!(title_from_file_name(Path::new("pages/a___b%3F.md")), "a/b?");
+        assert_eq!(
+            title_from_file_name(Path::new("journals/2024_01_03.md")),
+            "Jan 3rd, 2024"
+        );
+        assert_eq!(
+            title_from_file_name(Path::new("journals/2024_01.md")),
+            "2024-01"
+        );
+    }
+
+    #[test]
+    fn test_read_file_graph() {
+        let mut pages = read_file_graph("notes", &fixture_graph());
+        pages.sort_by(|(a, _), (b, _)| a.title.cmp(&b.title));
+
+        let tasks = file_graph_tasks("notes", &pages);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].page, "Jan 3rd, 2024");
+        assert!(tasks[0].updated_at.is_some());
+
+        let pages: Vec<LogseqPage> = pages.into_iter().map(|(page, _)| page).collect();
+
+        assert_eq!(
+            pages.iter().map(|p| p.title.as_str()).collect::<Vec<_>>(),
+            vec!["Jan 3rd, 2024", "Logseq", "삶"]
+        );
+        assert_eq!(tag_names(&pages[1]), vec!["tool", "pkm"]);
+        assert_eq!(pages[1].uuid, "65a1b2c3-0000-4000-8000-000000000001");
+        assert_eq!(tag_names(&pages[2]), vec!["philosophy"]);
+        assert!(pages.iter().all(|p| p.updated_at.is_some()));
+        assert_eq!(pages[0].journal_day, Some(20240103));
+        assert_eq!(pages[1].journal_day, None);
+    }
+}
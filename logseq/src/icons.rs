@@ -0,0 +1,213 @@
+use crate::cache::{LogseqIcon, LogseqTag};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Used when no tag of a page has an icon
+const FALLBACK_ICON: &str = "emoji:📝";
+/// Used for tasks whose tags have no icon
+const TODO_ICON: &str = "gnome-todo";
+
+/// Tags whose icons are never shown
+pub const DEFAULT_EXCLUDED_TAGS: &str = "Task,Page,DONE,TODAY,INBOX,SOMEDAY,DELEGATE,GTD-PROJECT";
+/// Tags marking tasks, shown with the todo icon when no other tag has one
+pub const DEFAULT_TODO_TAGS: &str = "Task,GTD,GTD-PROJECT";
+
+/// Picks the icon of a page or block from its tags. Emoji icons are shown as
+/// emoji, tabler icons with the bundled SVGs, recolored with the tag color.
+pub struct TagIcons {
+    /// Directory of the bundled tabler SVGs, named by icon id
+    dir: PathBuf,
+    icons: HashSet<String>,
+    /// Icon ids without an SVG that were already logged
+    missing: Mutex<HashSet<String>>,
+    /// Where recolored copies of the SVGs are written
+    colored_dir: PathBuf,
+    /// Tags whose icon is preferred over the icons of other tags, in order
+    pub priority_tags: Vec<String>,
+    pub excluded_tags: Vec<String>,
+    pub todo_tags: Vec<String>,
+}
+
+/// Names of a comma separated config value
+pub fn tag_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// `#e11d48` or `#fff`, other colors can't be written into an SVG safely
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+impl TagIcons {
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, colored_dir: Q) -> Self {
+        let icons = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().is_some_and(|e| e == "svg"))
+                    .filter_map(|p| Some(p.file_stem()?.to_string_lossy().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        TagIcons {
+            dir: dir.as_ref().to_path_buf(),
+            icons,
+            missing: Mutex::new(HashSet::new()),
+            colored_dir: colored_dir.as_ref().to_path_buf(),
+            priority_tags: vec![],
+            excluded_tags: tag_list(DEFAULT_EXCLUDED_TAGS),
+            todo_tags: tag_list(DEFAULT_TODO_TAGS),
+        }
+    }
+
+    pub fn icon_for_tags(&self, tags: &[LogseqTag]) -> String {
+        let contains = |list: &[String], tag: &LogseqTag| {
+            list.iter().any(|t| t.eq_ignore_ascii_case(&tag.name))
+        };
+        let priority = self
+            .priority_tags
+            .iter()
+            .filter_map(|name| tags.iter().find(|t| t.name.eq_ignore_ascii_case(name)));
+        let others = tags
+            .iter()
+            .filter(|tag| !contains(&self.excluded_tags, tag));
+
+        if let Some(icon) = priority
+            .chain(others)
+            .find_map(|tag| self.render(tag.icon.as_ref()?))
+        {
+            return icon;
+        }
+        if tags.iter().any(|tag| contains(&self.todo_tags, tag)) {
+            return TODO_ICON.to_string();
+        }
+        FALLBACK_ICON.to_string()
+    }
+
+    /// Icon findex can show, `None` for unknown icons
+    fn render(&self, icon: &LogseqIcon) -> Option<String> {
+        match icon.icon_type.as_str() {
+            "emoji" => emojis::get_by_shortcode(&icon.id)
+                .or_else(|| emojis::get(&icon.id))
+                .map(|emoji| format!("emoji:{}", emoji.as_str())),
+            "tabler-icon" => self
+                .tabler_icon(&icon.id, icon.color.as_deref())
+                .map(|path| path.to_string_lossy().to_string()),
+            _ => None,
+        }
+    }
+
+    /// Path of the SVG of a tabler icon, a copy drawn in `color` if it has one
+    fn tabler_icon(&self, id: &str, color: Option<&str>) -> Option<PathBuf> {
+        if !self.icons.contains(id) {
+            if self
+                .missing
+                .lock()
+                .is_ok_and(|mut m| m.insert(id.to_string()))
+            {
+                log::warn!("missing tabler icon id={:?} dir={:?}", id, self.dir);
+            }
+            return None;
+        }
+        let path = self.dir.join(format!("{}.svg", id));
+        let Some(color) = color.filter(|c| is_hex_color(c)) else {
+            return Some(path);
+        };
+
+        let colored = self.colored_dir.join(format!(
+            "{}-{}.svg",
+            id,
+            color.trim_start_matches('#').to_lowercase()
+        ));
+        if !colored.exists() {
+            // the bundled icons are drawn with `currentColor`
+            let svg = std::fs::read_to_string(&path).ok()?;
+            std::fs::create_dir_all(&self.colored_dir).ok()?;
+            std::fs::write(&colored, svg.replace("currentColor", color)).ok()?;
+        }
+        Some(colored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str, icon: Option<(&str, &str, Option<&str>)>) -> LogseqTag {
+        LogseqTag {
+            name: name.to_string(),
+            icon: icon.map(|(icon_type, id, color)| LogseqIcon {
+                icon_type: icon_type.to_string(),
+                id: id.to_string(),
+                color: color.map(str::to_string),
+            }),
+        }
+    }
+
+    fn bundled_icons(colored_dir: &Path) -> TagIcons {
+        TagIcons::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/logseq-icons"),
+            colored_dir,
+        )
+    }
+
+    #[test]
+    fn test_icon_for_tags() {
+        let colored_dir = tempfile::tempdir().unwrap();
+        let mut icons = bundled_icons(colored_dir.path());
+        let book = tag("Book", Some(("emoji", "books", None)));
+        let math = tag("Math", Some(("tabler-icon", "math-function", None)));
+
+        assert_eq!(icons.icon_for_tags(std::slice::from_ref(&book)), "emoji:📚");
+        assert_eq!(
+            icons.icon_for_tags(&[tag("note", None), math.clone()]),
+            icons.dir.join("math-function.svg").to_string_lossy()
+        );
+        assert_eq!(
+            icons.icon_for_tags(&[tag("Task", Some(("emoji", "books", None)))]),
+            "gnome-todo"
+        );
+        assert_eq!(
+            icons.icon_for_tags(&[tag("Idea", Some(("tabler-icon", "unknown", None)))]),
+            "emoji:📝"
+        );
+        assert!(icons.missing.lock().unwrap().contains("unknown"));
+
+        icons.priority_tags = vec!["math".to_string()];
+        icons.excluded_tags = vec!["book".to_string()];
+        icons.todo_tags = vec![];
+        assert_eq!(
+            icons.icon_for_tags(&[book.clone(), math]),
+            icons.dir.join("math-function.svg").to_string_lossy()
+        );
+        assert_eq!(icons.icon_for_tags(&[book, tag("Task", None)]), "emoji:📝");
+    }
+
+    #[test]
+    fn test_colored_tabler_icon() {
+        let colored_dir = tempfile::tempdir().unwrap();
+        let icons = bundled_icons(colored_dir.path());
+
+        let icon = icons.tabler_icon("math-function", Some("#E11D48")).unwrap();
+        assert_eq!(icon, colored_dir.path().join("math-function-e11d48.svg"));
+        let svg = std::fs::read_to_string(icon).unwrap();
+        assert!(svg.contains("stroke=\"#E11D48\""));
+        assert!(!svg.contains("currentColor"));
+
+        // colors that aren't hex codes are ignored
+        assert_eq!(
+            icons.tabler_icon("book", Some("var(--red)\"/><script")),
+            Some(icons.dir.join("book.svg"))
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+/// Title logseq gives the journal page of `date`, e.g. `Oct 19th, 2026`
+pub fn journal_title(date: NaiveDate) -> String {
+    let day = date.day();
+    let suffix = match (day % 10, day) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{} {}{}, {}", date.format("%b"), day, suffix, date.year())
+}
+
+/// `date` as logseq stores the day of journal pages, e.g. `20261019`
+pub fn journal_day(date: NaiveDate) -> u32 {
+    date.year() as u32 * 10000 + date.month() * 100 + date.day()
+}
+
+/// Date a query refers to, relative to `today`: `today`, `yesterday`,
+/// `tomorrow`, `2026-10-01`, `last monday` or `next fri`
+pub fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "today" | "오늘" => return Some(today),
+        "yesterday" | "어제" => return today.checked_sub_days(Days::new(1)),
+        "tomorrow" | "내일" => return today.checked_add_days(Days::new(1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let (direction, weekday) = text.split_once(char::is_whitespace)?;
+    let weekday: Weekday = weekday.trim().parse().ok()?;
+    let today_index = today.weekday().num_days_from_monday();
+    let index = weekday.num_days_from_monday();
+    // the same weekday as today is a week away
+    let days_until = |from: u32, to: u32| Days::new(((to + 6 - from) % 7 + 1).into());
+    match direction {
+        "last" => today.checked_sub_days(days_until(index, today_index)),
+        "next" => today.checked_add_days(days_until(today_index, index)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_journal_title() {
+        assert_eq!(journal_title(date(2026, 10, 19)), "Oct 19th, 2026");
+        assert_eq!(journal_title(date(2026, 1, 1)), "Jan 1st, 2026");
+        assert_eq!(journal_title(date(2026, 3, 22)), "Mar 22nd, 2026");
+        assert_eq!(journal_title(date(2026, 5, 13)), "May 13th, 2026");
+        assert_eq!(journal_title(date(2026, 7, 23)), "Jul 23rd, 2026");
+    }
+
+    #[test]
+    fn test_parse_date() {
+        // a monday
+        let today = date(2026, 10, 19);
+
+        assert_eq!(journal_day(today), 20261019);
+        assert_eq!(parse_date("Today", today), Some(today));
+        assert_eq!(parse_date("yesterday", today), Some(date(2026, 10, 18)));
+        assert_eq!(parse_date("내일", today), Some(date(2026, 10, 20)));
+        assert_eq!(parse_date("2026-10-01", today), Some(date(2026, 10, 1)));
+        assert_eq!(parse_date("last monday", today), Some(date(2026, 10, 12)));
+        assert_eq!(parse_date("last sunday", today), Some(date(2026, 10, 18)));
+        assert_eq!(parse_date("last tue", today), Some(date(2026, 10, 13)));
+        assert_eq!(parse_date("next monday", today), Some(date(2026, 10, 26)));
+        assert_eq!(parse_date("next friday", today), Some(date(2026, 10, 23)));
+        assert_eq!(parse_date("rust", today), None);
+        assert_eq!(parse_date("last week", today), None);
+        assert_eq!(parse_date("2026-13-01", today), None);
+    }
+}
@@ -1,20 +1,207 @@
+mod api;
 mod cache;
+mod edn;
+mod file_graph;
+mod icons;
+mod journal;
+mod logger;
 mod logseq;
+mod query;
+mod ranking;
+mod status;
+mod sync;
+mod tasks;
+mod usage;
+mod watcher;
 
 use abi_stable::std_types::*;
-use cache::{FilePageCache, LogseqTag};
+use api::{ApiRequest, ApiSource, LogseqApi};
+use cache::{
+    FileBlockCache, FilePageCache, FileTaskCache, LogseqBlockEntry, LogseqPage, LogseqTag,
+    LogseqTaskEntry,
+};
+use chrono::{Local, NaiveDate, Utc};
 use findex_plugin::{define_plugin, ApplicationCommand, FResult};
+use icons::TagIcons;
+use journal::journal_title;
+use log::LevelFilter;
+use logger::RotatingFileLogger;
+use logseq::{LogseqCli, LogseqSource};
+use query::Query;
 use rand::rng;
 use rand::seq::SliceRandom;
-use std::{thread, time::Duration};
+use ranking::{rank_pages, usage_key};
+use shellexpand::tilde;
+use status::FileSyncStatus;
+use std::{path::PathBuf, sync::OnceLock, thread, time::Duration};
+use sync::GraphSync;
+use usage::FileUsageCache;
 
-fn init(_: &RHashMap<RString, RString>) -> RResult<(), RString> {
-    thread::spawn(move || loop {
-        if let Ok(pages) = logseq::get_logseq_pages() {
-            let cache = FilePageCache::default();
-            if let Err(e) = cache.update_cache(pages) {
-                eprintln!("Failed to update logseq cache: {}", e);
+const DEFAULT_PROPERTIES: &str = "status,type";
+/// Graph synced before the graph name was configurable
+const DEFAULT_GRAPH: &str = "illef";
+const LOG_FILE: &str = "~/.cache/illef-findex-plugin/logseq.log";
+const DEFAULT_ICON_DIR: &str = "~/.cache/illef-findex-plugin/logseq-icons";
+const COLORED_ICON_DIR: &str = "~/.cache/illef-findex-plugin/logseq-icons-colored";
+const OPEN_SCRIPT: &str = "~/.cache/illef-findex-plugin/scripts/logseq_open.sh";
+
+static GRAPHS: OnceLock<Vec<String>> = OnceLock::new();
+/// Properties searchable with `name:value`
+static PROPERTIES: OnceLock<Vec<String>> = OnceLock::new();
+/// Shows random pages after the best ones when the query is empty
+static DISCOVERY: OnceLock<bool> = OnceLock::new();
+/// Used to create pages and capture blocks, only set when a token is configured
+static API: OnceLock<LogseqApi> = OnceLock::new();
+static ICONS: OnceLock<TagIcons> = OnceLock::new();
+
+fn init(config: &RHashMap<RString, RString>) -> RResult<(), RString> {
+    let log_level = config
+        .get("log-level")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(LevelFilter::Info);
+    let _ = RotatingFileLogger::new(&*tilde(LOG_FILE), log_level).install();
+    cache::remove_legacy_cache();
+
+    // comma separated graph names
+    let mut graphs: Vec<String> = config
+        .get("graph")
+        .map(|g| g.to_string())
+        .unwrap_or_default()
+        .split(',')
+        .map(|g| g.trim().to_string())
+        .filter(|g| !g.is_empty())
+        .collect();
+    // comma separated `<name>=<directory>` of file based graphs
+    let file_graphs: Vec<(String, PathBuf)> = config
+        .get("file-graphs")
+        .map(|g| g.to_string())
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|g| {
+            let (name, dir) = g.split_once('=')?;
+            Some((name.trim().to_string(), PathBuf::from(&*tilde(dir.trim()))))
+        })
+        .collect();
+    // comma separated graphs read through the HTTP API instead of the CLI,
+    // they don't have to be listed in `graph` as well
+    let api_graphs: Vec<String> = config
+        .get("api-graphs")
+        .map(|g| {
+            g.split(',')
+                .map(|g| g.trim().to_string())
+                .filter(|g| !g.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    for graph in &api_graphs {
+        if !graphs.contains(graph) {
+            graphs.push(graph.clone());
+        }
+    }
+    if graphs.is_empty() && file_graphs.is_empty() {
+        log::warn!("no graph config, using graph={:?}", DEFAULT_GRAPH);
+        graphs.push(DEFAULT_GRAPH.to_string());
+    }
+
+    let icon_dir = config
+        .get("icon-dir")
+        .map(|d| d.to_string())
+        .unwrap_or(DEFAULT_ICON_DIR.to_string());
+    let mut icons = TagIcons::new(&*tilde(&icon_dir), &*tilde(COLORED_ICON_DIR));
+    // comma separated tag names
+    if let Some(tags) = config.get("priority-tags") {
+        icons.priority_tags = icons::tag_list(tags);
+    }
+    if let Some(tags) = config.get("excluded-tags") {
+        icons.excluded_tags = icons::tag_list(tags);
+    }
+    if let Some(tags) = config.get("todo-tags") {
+        icons.todo_tags = icons::tag_list(tags);
+    }
+    let _ = ICONS.set(icons);
+
+    if let Some(token) = config.get("api-token") {
+        let api = LogseqApi::new(
+            config
+                .get("api-url")
+                .map(|u| u.to_string())
+                .unwrap_or(LogseqApi::DEFAULT_URL.to_string()),
+            token.to_string(),
+        );
+        if let Err(e) = api.write_header_file() {
+            log::error!(
+                "api header write failed file={:?} error={:?}",
+                api.header_file,
+                e.to_string()
+            );
+        }
+        let _ = API.set(api);
+    }
+
+    let mut cli = LogseqCli::default();
+    if let Some(command) = config.get("cli-command") {
+        cli.command = command.to_string();
+    }
+    let mut sources: Vec<Box<dyn LogseqSource + Send>> = vec![];
+    for graph in &graphs {
+        match API.get() {
+            Some(api) if api_graphs.contains(graph) => sources.push(Box::new(ApiSource {
+                graph: graph.clone(),
+                api: api.clone(),
+            })),
+            None if api_graphs.contains(graph) => {
+                return RErr(RString::from("Cannot find api-token config"));
             }
+            _ => sources.push(Box::new(LogseqCli {
+                graph: graph.clone(),
+                ..cli.clone()
+            })),
+        }
+    }
+    let _ = GRAPHS.set(
+        graphs
+            .into_iter()
+            .chain(file_graphs.iter().map(|(name, _)| name.clone()))
+            .collect(),
+    );
+
+    for (graph, dir) in file_graphs {
+        thread::spawn(move || {
+            // watched graphs are synced when they change, they don't get stale
+            let mut status = FileSyncStatus::for_graph(&graph);
+            let mut sync = || {
+                let result = sync::sync_file_graph(&graph, &dir);
+                sync::record(&mut status, &graph, &result, None);
+            };
+            sync();
+            if let Err(e) = watcher::watch(&dir, Duration::from_secs(1), sync) {
+                log::error!("watch failed graph={:?} error={:?}", graph, e.to_string());
+            }
+        });
+    }
+    // comma separated properties shown and searchable with `name:value`
+    let property_names = parse_property_names(
+        &config
+            .get("properties")
+            .map(|p| p.to_string())
+            .unwrap_or(DEFAULT_PROPERTIES.to_string()),
+    );
+    let _ = PROPERTIES.set(property_names.clone());
+    let _ = DISCOVERY.set(
+        config
+            .get("discovery")
+            .is_some_and(|d| d.as_str() == "true"),
+    );
+    // blocks are only synced on demand, there are a lot more of them than pages
+    let sync_blocks = config.get("blocks").is_some_and(|b| b.as_str() == "true");
+
+    let mut syncs: Vec<GraphSync> = sources.into_iter().map(GraphSync::new).collect();
+    thread::spawn(move || loop {
+        for sync in &mut syncs {
+            sync.sync(&property_names, sync_blocks);
+        }
+        if let Err(e) = FileUsageCache::default().update_usage() {
+            log::warn!("usage update failed error={:?}", e.to_string());
         }
         thread::sleep(Duration::from_secs(60));
     });
@@ -22,119 +209,504 @@ fn init(_: &RHashMap<RString, RString>) -> RResult<(), RString> {
     ROk(())
 }
 
-fn handle_query(query: RStr) -> RVec<FResult> {
-    let search_term = query.as_str().to_lowercase();
-    let cache = FilePageCache::default();
+fn parse_property_names(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
 
-    match cache.load_cache() {
-        Ok(mut pages) => {
-            // Sort pages by updated_at in descending order (most recent first)
-            pages.sort_by(|a, b| match (a.updated_at, b.updated_at) {
-                (Some(a_time), Some(b_time)) => b_time.cmp(&a_time),
-                (Some(_), None) => std::cmp::Ordering::Less,
-                (None, Some(_)) => std::cmp::Ordering::Greater,
-                (None, None) => std::cmp::Ordering::Equal,
-            });
+fn property_names() -> &'static [String] {
+    PROPERTIES.get_or_init(|| parse_property_names(DEFAULT_PROPERTIES))
+}
 
-            // Take top 5 most recently updated pages and shuffle the rest
-            let pages_len = pages.len();
-            let split_index = if pages_len > 5 { 5 } else { pages_len };
+/// Cached pages of the graphs the query is limited to
+fn load_pages(query: &Query) -> Vec<LogseqPage> {
+    GRAPHS
+        .get()
+        .into_iter()
+        .flatten()
+        .filter(|graph| query.matches_graph(graph))
+        .flat_map(|graph| {
+            FilePageCache::for_graph(graph)
+                .load_cache()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|page| LogseqPage {
+                    graph: graph.clone(),
+                    ..page
+                })
+        })
+        .collect()
+}
 
-            let (recent_pages, remaining_pages) = pages.split_at_mut(split_index);
+/// Cached blocks of the graphs the query is limited to
+fn load_blocks(query: &Query) -> Vec<LogseqBlockEntry> {
+    GRAPHS
+        .get()
+        .into_iter()
+        .flatten()
+        .filter(|graph| query.matches_graph(graph))
+        .flat_map(|graph| {
+            FileBlockCache::for_graph(graph)
+                .load_cache()
+                .unwrap_or_default()
+        })
+        .collect()
+}
 
-            // Shuffle the remaining pages
-            remaining_pages.shuffle(&mut rng());
+/// Cached tasks of the graphs the query is limited to
+fn load_tasks(query: &Query) -> Vec<LogseqTaskEntry> {
+    GRAPHS
+        .get()
+        .into_iter()
+        .flatten()
+        .filter(|graph| query.matches_graph(graph))
+        .flat_map(|graph| {
+            FileTaskCache::for_graph(graph)
+                .load_cache()
+                .unwrap_or_default()
+        })
+        .collect()
+}
 
-            // Combine recent pages (first 5) with shuffled remaining pages
-            let mut sorted_pages = recent_pages.to_vec();
-            sorted_pages.extend_from_slice(remaining_pages);
+/// Opens `url` through `logseq_open.sh`, which records the access for ranking
+fn open_command(key: &str, url: &str) -> ApplicationCommand {
+    ApplicationCommand::Command(RString::from(format!(
+        "{} {} {}",
+        tilde(OPEN_SCRIPT),
+        shell_quote(key),
+        shell_quote(url)
+    )))
+}
 
-            let filtered_pages: Vec<_> = sorted_pages
-                .into_iter()
-                .filter(|page| {
-                    if search_term.is_empty() {
-                        true
-                    } else {
-                        page.title.to_lowercase().contains(&search_term)
-                            || page.tags.iter().any(|tag| {
-                                tag.name.to_string().to_lowercase().contains(&search_term)
-                            })
-                    }
-                })
-                .take(15)
-                .collect();
-
-            RVec::from(
-                filtered_pages
-                    .into_iter()
-                    .map(|page| {
-                        let desc = if page.tags.is_empty() {
-                            RNone
-                        } else {
-                            RSome(RString::from(
-                                page.tags
-                                    .iter()
-                                    .filter(|tag| &tag.name != &"Page")
-                                    .map(|tag| format!("#{}", tag.name))
-                                    .collect::<Vec<String>>()
-                                    .join(" "),
-                            ))
-                        };
-
-                        let icon = get_icon_for_tags(&page.tags);
-
-                        FResult {
-                            cmd: ApplicationCommand::Command(RString::from(format!(
-                                "bash -c 'xdg-open logseq://graph/illef?block-id={}'",
-                                page.uuid
-                            ))),
-                            icon: RString::from(icon),
-                            score: isize::MAX,
-                            name: RString::from(page.title),
-                            desc,
-                        }
-                    })
-                    .collect::<Vec<_>>(),
-            )
-        }
-        Err(_) => RVec::new(),
+fn open_block_command(graph: &str, uuid: &str) -> ApplicationCommand {
+    open_command(
+        &usage_key(graph, uuid, ""),
+        &format!("logseq://graph/{}?block-id={}", graph, uuid),
+    )
+}
+
+/// Pages of file based graphs only have an uuid when it was referenced, they
+/// are opened by name otherwise
+fn open_page_command(page: &LogseqPage) -> ApplicationCommand {
+    if !page.uuid.is_empty() {
+        return open_block_command(&page.graph, &page.uuid);
     }
+    open_page_title_command(&page.graph, &page.title)
 }
 
-fn get_icon_for_tags(tags: &[LogseqTag]) -> String {
-    if let Some(tag) = tags
-        .iter()
-        .filter(|t| {
-            let excluded = [
-                "Task",
-                "Page",
-                "DONE",
-                "TODAY",
-                "INBOX",
-                "SOMEDAY",
-                "DELEGATE",
-                "GTD-PROJECT",
-            ];
-            !excluded.contains(&t.name.as_str())
-        })
-        .next()
-    {
-        if let Some(icon) = &tag.icon {
-            if icon.icon_type == "emoji" {
-                if let Some(emoji) = emojis::get_by_shortcode(&icon.id) {
-                    return format!("emoji:{}", emoji.as_str());
-                }
+fn open_page_title_command(graph: &str, title: &str) -> ApplicationCommand {
+    open_command(
+        &usage_key(graph, "", title),
+        &format!("logseq://graph/{}?page={}", graph, percent_encode(title)),
+    )
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
             }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn search_blocks(query: &Query) -> Vec<FResult> {
+    if query.text.is_empty() {
+        return vec![];
+    }
+
+    let mut blocks: Vec<_> = load_blocks(query)
+        .into_iter()
+        .filter(|block| query.matches_words(&block.content))
+        .collect();
+    blocks.sort_by_key(|block| std::cmp::Reverse(block.updated_at));
+
+    blocks
+        .into_iter()
+        .take(15)
+        .map(|block| FResult {
+            cmd: open_block_command(&block.graph, &block.uuid),
+            icon: RString::from(get_icon_for_tags(&block.tags)),
+            score: isize::MAX,
+            name: RString::from(
+                block
+                    .content
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .take(120)
+                    .collect::<String>(),
+            ),
+            desc: RSome(RString::from(format!("[{}] {}", block.graph, block.page))),
+        })
+        .collect()
+}
+
+fn search_tasks(query: &Query) -> Vec<FResult> {
+    tasks::filter_tasks(load_tasks(query), query, Local::now().date_naive())
+        .into_iter()
+        .take(15)
+        .map(|task| FResult {
+            cmd: if task.uuid.is_empty() {
+                open_page_title_command(&task.graph, &task.page)
+            } else {
+                open_block_command(&task.graph, &task.uuid)
+            },
+            icon: RString::from(match task.status.as_deref() {
+                Some("done") => "emoji:✅",
+                Some("canceled") => "emoji:❌",
+                _ => "gnome-todo",
+            }),
+            score: isize::MAX,
+            desc: RSome(RString::from(task_description(&task))),
+            name: RString::from(
+                task.content
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .take(120)
+                    .collect::<String>(),
+            ),
+        })
+        .collect()
+}
+
+/// `[graph] page | doing | priority: high | due: Oct 21st, 2026`
+fn task_description(task: &LogseqTaskEntry) -> String {
+    let location = format!("[{}] {}", task.graph, task.page);
+    let priority = task.priority.as_ref().map(|p| format!("priority: {}", p));
+    let due = tasks::due_date(task).map(|date| format!("due: {}", journal_title(date)));
+
+    std::iter::once(location)
+        .chain(task.status.clone())
+        .chain(priority)
+        .chain(due)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Rows explaining why pages of the graphs may be missing or outdated
+fn sync_status_results(query: &Query) -> Vec<FResult> {
+    let now = Utc::now().timestamp_millis();
+    GRAPHS
+        .get()
+        .into_iter()
+        .flatten()
+        .filter(|graph| query.matches_graph(graph))
+        .filter_map(|graph| {
+            let status = FileSyncStatus::for_graph(graph).load();
+            let problem = status.problem(graph, now)?;
+            let log_file = tilde(LOG_FILE);
+            Some(FResult {
+                cmd: ApplicationCommand::Command(RString::from(format!(
+                    "xdg-open {}",
+                    shell_quote(&log_file)
+                ))),
+                icon: RString::from("dialog-warning"),
+                score: isize::MAX,
+                name: RString::from(problem),
+                desc: RSome(RString::from(match status.last_error {
+                    Some(e) if status.last_error_at > status.last_success => e,
+                    _ => format!("Open {} for details", log_file),
+                })),
+            })
+        })
+        .collect()
+}
+
+fn missing_api_result() -> FResult {
+    FResult {
+        cmd: ApplicationCommand::None,
+        icon: RString::from("dialog-warning"),
+        score: isize::MAX,
+        name: RString::from("Set api-token to create pages from the launcher"),
+        desc: RSome(RString::from(
+            "Enable the HTTP API server in logseq and add its token to the plugin config",
+        )),
+    }
+}
+
+/// Graph open in logseq when it isn't the `graph:` of the query. The API
+/// only writes to the open graph, so nothing is created for such a query.
+fn other_open_graph(api: &LogseqApi, query: &Query) -> Option<String> {
+    let graph = query.graph.as_ref()?;
+    let open = api.current_graph().ok().flatten();
+    match open.as_ref().map(|g| g.graph_name().to_lowercase()) {
+        Some(open) if open == *graph => None,
+        open => Some(open.unwrap_or_else(|| "no graph".to_string())),
+    }
+}
+
+/// Appends `text` as a block to today's journal
+fn capture_result(query: &Query, text: &str) -> FResult {
+    let Some(api) = API.get() else {
+        return missing_api_result();
+    };
+    if let Some(open) = other_open_graph(api, query) {
+        return FResult {
+            cmd: ApplicationCommand::None,
+            icon: RString::from("dialog-warning"),
+            score: isize::MAX,
+            name: RString::from(format!(
+                "Open graph {} in logseq to capture into it",
+                query.graph.as_deref().unwrap_or_default()
+            )),
+            desc: RSome(RString::from(format!("{} is open", open))),
+        };
+    }
+    let journal = journal_title(Local::now().date_naive());
+
+    FResult {
+        cmd: ApplicationCommand::Command(RString::from(
+            api.command(&ApiRequest::append_block(&journal, text)),
+        )),
+        icon: RString::from("emoji:📥"),
+        score: isize::MAX,
+        name: RString::from(format!("Capture '{}'", text)),
+        desc: RSome(RString::from(format!("Append to {}", journal))),
+    }
+}
+
+/// Creates the journal of `date` when the graph doesn't have one yet
+fn create_journal_result(query: &Query, date: NaiveDate) -> Option<FResult> {
+    let api = API.get()?;
+    if other_open_graph(api, query).is_some() {
+        return None;
+    }
+    let title = journal_title(date);
+    Some(FResult {
+        cmd: ApplicationCommand::Command(RString::from(
+            api.command(&ApiRequest::create_journal(&title)),
+        )),
+        icon: RString::from("emoji:📅"),
+        score: isize::MAX,
+        name: RString::from(format!("Create journal '{}'", title)),
+        desc: RSome(RString::from("Created in the graph open in logseq")),
+    })
+}
+
+/// Creates the page the query didn't find, logseq opens it afterwards
+fn create_page_result(query: &Query) -> Option<FResult> {
+    let Some(api) = API.get() else {
+        return Some(missing_api_result());
+    };
+    if other_open_graph(api, query).is_some() {
+        return None;
+    }
+    Some(FResult {
+        cmd: ApplicationCommand::Command(RString::from(
+            api.command(&ApiRequest::create_page(&query.input)),
+        )),
+        icon: RString::from("emoji:➕"),
+        score: isize::MAX,
+        name: RString::from(format!("Create page '{}'", query.input)),
+        desc: RSome(RString::from("Created in the graph open in logseq")),
+    })
+}
+
+fn handle_query(query: RStr) -> RVec<FResult> {
+    let query = Query::parse(query.as_str(), property_names());
+    if let Some(text) = &query.capture {
+        if text.is_empty() {
+            return RVec::new();
         }
+        return RVec::from(vec![capture_result(&query, text)]);
+    }
+    if query.tasks {
+        return RVec::from(search_tasks(&query));
     }
+    if query.blocks {
+        return RVec::from(search_blocks(&query));
+    }
+    // `today`, `last monday`, ... show the journal of that day first
+    let date = journal::parse_date(&query.text, Local::now().date_naive());
+    let day = date.map(journal::journal_day);
+    let (journals, pages): (Vec<LogseqPage>, Vec<LogseqPage>) = load_pages(&query)
+        .into_iter()
+        .filter(|page| query.matches_properties(&page.properties))
+        .partition(|page| day.is_some() && page.journal_day == day);
+    let usage = FileUsageCache::default().load_usage();
+    let mut ranked_pages = rank_pages(pages, &query.text, &usage, Utc::now().timestamp_millis());
 
-    if tags
-        .iter()
-        .any(|tag| matches!(tag.name.as_str(), "Task" | "GTD" | "GTD-PROJECT"))
-    {
-        return "gnome-todo".to_string();
+    if query.text.is_empty() && DISCOVERY.get().is_some_and(|d| *d) {
+        // keep the 5 best pages and surface random ones after them
+        let split_index = ranked_pages.len().min(5);
+        ranked_pages[split_index..].shuffle(&mut rng());
     }
-    "emoji:📝".to_string()
+
+    let mut results: Vec<FResult> = vec![];
+    if let Some(date) = date.filter(|_| journals.is_empty()) {
+        results.extend(create_journal_result(&query, date));
+    }
+    results.extend(
+        journals
+            .into_iter()
+            .chain(ranked_pages)
+            .take(15)
+            .map(page_result),
+    );
+
+    if results.is_empty() && !query.input.is_empty() {
+        results.extend(create_page_result(&query));
+    }
+    results.extend(sync_status_results(&query));
+    RVec::from(results)
+}
+
+fn page_result(page: LogseqPage) -> FResult {
+    FResult {
+        cmd: open_page_command(&page),
+        icon: RString::from(get_icon_for_tags(&page.tags)),
+        score: isize::MAX,
+        desc: RSome(RString::from(page_description(&page))),
+        name: RString::from(page.title),
+    }
+}
+
+/// `[graph] #tags | alias: ... | status: ...`
+fn page_description(page: &LogseqPage) -> String {
+    let tags = std::iter::once(format!("[{}]", page.graph))
+        .chain(
+            page.tags
+                .iter()
+                .filter(|tag| tag.name != "Page")
+                .map(|tag| format!("#{}", tag.name)),
+        )
+        .collect::<Vec<String>>()
+        .join(" ");
+    let aliases = (!page.aliases.is_empty()).then(|| format!("alias: {}", page.aliases.join(", ")));
+    let properties = page
+        .properties
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value));
+
+    std::iter::once(tags)
+        .chain(aliases)
+        .chain(properties)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn get_icon_for_tags(tags: &[LogseqTag]) -> String {
+    ICONS
+        .get_or_init(|| TagIcons::new(&*tilde(DEFAULT_ICON_DIR), &*tilde(COLORED_ICON_DIR)))
+        .icon_for_tags(tags)
+}
+
+pub(crate) fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
 }
 
 define_plugin!("logseq!", init, handle_query);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_page_command() {
+        let page = LogseqPage {
+            graph: "notes".to_string(),
+            title: "Rust/Async it's".to_string(),
+            ..Default::default()
+        };
+        let command = |page: &LogseqPage| match open_page_command(page) {
+            ApplicationCommand::Command(c) => c.to_string(),
+            _ => unreachable!(),
+        };
+
+        let script = tilde(OPEN_SCRIPT);
+
+        assert_eq!(
+            command(&page),
+            format!(
+                "{} 'notes/Rust/Async it'\\''s' 'logseq://graph/notes?page=Rust%2FAsync%20it%27s'",
+                script
+            )
+        );
+        assert_eq!(
+            command(&LogseqPage {
+                uuid: "67c56741".to_string(),
+                ..page
+            }),
+            format!(
+                "{} 'notes/67c56741' 'logseq://graph/notes?block-id=67c56741'",
+                script
+            )
+        );
+    }
+
+    #[test]
+    fn test_task_description() {
+        let task = LogseqTaskEntry {
+            graph: "illef".to_string(),
+            content: "Write the plugin README".to_string(),
+            uuid: String::new(),
+            page: "Work".to_string(),
+            status: Some("doing".to_string()),
+            priority: Some("high".to_string()),
+            scheduled: None,
+            deadline: NaiveDate::from_ymd_opt(2026, 10, 21)
+                .and_then(|d| d.and_hms_opt(9, 0, 0))
+                .and_then(|t| t.and_local_timezone(Local).earliest())
+                .map(|t| t.timestamp_millis()),
+            tags: vec![],
+            updated_at: None,
+        };
+
+        assert_eq!(
+            task_description(&task),
+            "[illef] Work | doing | priority: high | due: Oct 21st, 2026"
+        );
+        assert_eq!(
+            task_description(&LogseqTaskEntry {
+                status: None,
+                priority: None,
+                deadline: None,
+                ..task
+            }),
+            "[illef] Work"
+        );
+    }
+
+    #[test]
+    fn test_page_description() {
+        let page = LogseqPage {
+            graph: "illef".to_string(),
+            title: "삶".to_string(),
+            tags: vec![
+                LogseqTag {
+                    name: "Page".to_string(),
+                    icon: None,
+                },
+                LogseqTag {
+                    name: "essay".to_string(),
+                    icon: None,
+                },
+            ],
+            aliases: vec!["life".to_string()],
+            properties: [("status".to_string(), "Doing".to_string())].into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            page_description(&page),
+            "[illef] #essay | alias: life | status: Doing"
+        );
+        assert_eq!(
+            page_description(&LogseqPage {
+                aliases: vec![],
+                properties: Default::default(),
+                ..page
+            }),
+            "[illef] #essay"
+        );
+    }
+}
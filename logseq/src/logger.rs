@@ -0,0 +1,156 @@
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Size after which the log file is rotated
+const MAX_BYTES: u64 = 1024 * 1024;
+/// Rotated files kept next to the log, `logseq.log.1` being the newest
+const KEEP: usize = 3;
+
+/// Writes `<time> <level> <target> key=value ...` lines to a log file, which
+/// is rotated once it grows past `MAX_BYTES`
+pub struct RotatingFileLogger {
+    path: PathBuf,
+    level: LevelFilter,
+    file: Mutex<Option<File>>,
+}
+
+impl RotatingFileLogger {
+    pub fn new<P: AsRef<Path>>(path: P, level: LevelFilter) -> Self {
+        RotatingFileLogger {
+            path: path.as_ref().to_path_buf(),
+            level,
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Makes this the logger of the `log` macros
+    pub fn install(self) -> Result<(), log::SetLoggerError> {
+        let level = self.level;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        for index in (1..KEEP).rev() {
+            let from = self.rotated(index);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated(index + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated(1))
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size >= MAX_BYTES {
+            *file = None;
+            self.rotate()?;
+        }
+
+        if file.is_none() {
+            if let Some(parent_dir) = self.path.parent() {
+                std::fs::create_dir_all(parent_dir)?;
+            }
+            *file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        match file.as_mut() {
+            Some(file) => writeln!(file, "{}", line),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Log for RotatingFileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {} {} {}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if let Err(e) = self.write_line(&line) {
+            eprintln!("Failed to write logseq log {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn log(logger: &RotatingFileLogger, level: Level, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target("logseq::sync")
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_log_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs/logseq.log");
+        let logger = RotatingFileLogger::new(&path, LevelFilter::Info);
+
+        log(&logger, Level::Info, "synced graph=\"illef\" pages=3");
+        log(&logger, Level::Debug, "hidden");
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(" INFO logseq::sync synced graph=\"illef\" pages=3"));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logseq.log");
+        let logger = RotatingFileLogger::new(&path, LevelFilter::Info);
+
+        let message = "x".repeat(MAX_BYTES as usize / 2);
+        // every other write rotates the file
+        for _ in 0..(KEEP + 2) * 2 + 1 {
+            log(&logger, Level::Warn, &message);
+        }
+
+        assert!(std::fs::metadata(&path).unwrap().len() < MAX_BYTES);
+        assert!(logger.rotated(KEEP).exists());
+        assert!(!logger.rotated(KEEP + 1).exists());
+    }
+}
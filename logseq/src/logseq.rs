@@ -1,107 +1,748 @@
-use crate::cache::{LogseqIcon, LogseqPage, LogseqTag};
+use crate::{
+    cache::{LogseqBlockEntry, LogseqIcon, LogseqPage, LogseqTag, LogseqTaskEntry},
+    edn,
+};
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    process::Command,
+};
 
+/// Block pulled by a query, the CLI uses the attribute names and the HTTP API
+/// their camel cased names without namespace
 #[derive(Debug, Deserialize, Serialize)]
 pub struct LogseqBlock {
-    #[serde(rename = "db/id")]
+    #[serde(rename = "db/id", alias = "id")]
     pub id: Option<i64>,
-    #[serde(rename = "block/title")]
+    #[serde(rename = "block/title", alias = "title")]
     pub title: Option<String>,
-    #[serde(rename = "block/uuid")]
+    #[serde(rename = "block/uuid", alias = "uuid")]
     pub uuid: Option<String>,
-    #[serde(rename = "block/tags")]
+    #[serde(rename = "block/tags", alias = "tags")]
     pub tags: Option<Vec<TagRef>>,
-    #[serde(rename = "block/updated-at")]
+    #[serde(rename = "block/updated-at", alias = "updatedAt")]
     pub updated_at: Option<i64>,
-    #[serde(rename = "logseq.property/icon")]
+    #[serde(rename = "logseq.property/icon", alias = "icon")]
     pub icon: Option<LogseqIcon>,
+    #[serde(rename = "block/page", alias = "page")]
+    pub page: Option<Box<LogseqBlock>>,
+    #[serde(rename = "block/alias", alias = "alias")]
+    pub aliases: Option<Vec<LogseqBlock>>,
+    #[serde(rename = "block/journal-day", alias = "journalDay")]
+    pub journal_day: Option<u32>,
+    #[serde(rename = "logseq.property/status", alias = "status")]
+    pub status: Option<Box<LogseqBlock>>,
+    #[serde(rename = "logseq.property/priority", alias = "priority")]
+    pub priority: Option<Box<LogseqBlock>>,
+    #[serde(rename = "logseq.property/scheduled", alias = "scheduled")]
+    pub scheduled: Option<i64>,
+    #[serde(rename = "logseq.property/deadline", alias = "deadline")]
+    pub deadline: Option<i64>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TagRef {
-    #[serde(rename = "db/id")]
+    #[serde(rename = "db/id", alias = "id")]
     pub id: Option<i64>,
 }
 
-pub fn get_logseq_pages() -> Result<Vec<LogseqPage>, String> {
-    let output = Command::new("bash")
-        .arg("-c")
-        .arg("npx @logseq/cli query illef '[:find (pull ?b [:block/tags :block/uuid :block/title :block/updated-at]) :where [?tag :block/name \"page\"] [?b :block/tags ?tag]]' | jet --to json")
-        .output()
-        .map_err(|e| format!("Failed to execute command: {}", e))?;
+/// Where the blocks of a graph are queried from
+pub trait LogseqSource {
+    fn graph(&self) -> &str;
+    /// Whether the graph can be queried now, syncs of closed graphs are skipped
+    fn is_open(&self) -> Result<bool, String> {
+        Ok(true)
+    }
+    /// Result of the query as JSON
+    fn query(&self, datalog: &str) -> Result<Value, String>;
+}
+
+/// How the logseq CLI is run for a graph
+#[derive(Debug, Clone)]
+pub struct LogseqCli {
+    pub graph: String,
+    /// Command the `query <graph> <datalog>` arguments are appended to,
+    /// split into words like a shell does
+    pub command: String,
+}
 
-    if !output.status.success() {
-        return Err(format!(
-            "Command failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+impl Default for LogseqCli {
+    fn default() -> Self {
+        LogseqCli {
+            graph: String::new(),
+            command: "npx @logseq/cli".to_string(),
+        }
     }
+}
 
-    let json_str =
-        String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 in output: {}", e))?;
+impl LogseqCli {
+    fn command(&self, datalog: &str) -> Result<Command, String> {
+        let words = shell_words::split(&self.command)
+            .map_err(|e| format!("Invalid logseq cli command: {}", e))?;
+        let mut words = words.into_iter();
+        let program = words
+            .next()
+            .ok_or_else(|| "Empty logseq cli command".to_string())?;
 
-    let blocks: Vec<LogseqBlock> =
-        serde_json::from_str(&json_str).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        let mut command = Command::new(program);
+        command
+            .args(words)
+            .arg("query")
+            .arg(&self.graph)
+            .arg(datalog);
+        Ok(command)
+    }
+}
 
-    let tag_blocks = match get_logseq_tag_blocks() {
-        Ok(blocks) => blocks,
-        Err(_) => Vec::new(),
-    };
+impl LogseqSource for LogseqCli {
+    fn graph(&self) -> &str {
+        &self.graph
+    }
+
+    fn query(&self, datalog: &str) -> Result<Value, String> {
+        let output = self
+            .command(datalog)?
+            .output()
+            .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        let edn_str = String::from_utf8(output.stdout)
+            .map_err(|e| format!("Invalid UTF-8 in output: {}", e))?;
 
-    let pages: Vec<LogseqPage> = blocks
+        parse_edn(&edn_str)
+    }
+}
+
+fn parse_edn(edn_str: &str) -> Result<Value, String> {
+    let value = edn::parse(edn_str).map_err(|e| format!("Failed to parse EDN: {}", e))?;
+    Ok(value.into_json())
+}
+
+/// `:where` clauses binding `?b` to pages, journals included
+pub const PAGES: &str =
+    "[?tag :block/name ?tag-name] [(contains? #{\"page\" \"journal\"} ?tag-name)] [?b :block/tags ?tag]";
+/// `:where` clauses binding `?b` to non empty blocks inside pages
+pub const BLOCKS: &str = "[?b :block/page ?p] [?b :block/title ?title] [(not= ?title \"\")]";
+/// `:where` clauses binding `?b` to blocks tagged with `#Task`
+pub const TASKS: &str = "[?t :db/ident :logseq.class/Task] [?b :block/tags ?t]";
+
+/// `:where` clauses of the blocks, only the ones updated after `since` when given
+fn where_clauses(blocks: &str, since: Option<i64>) -> String {
+    match since {
+        Some(since) => format!(
+            "{} [?b :block/updated-at ?updated-at] [(> ?updated-at {})]",
+            blocks, since
+        ),
+        None => blocks.to_string(),
+    }
+}
+
+/// Uuids of all the blocks, to find the ones deleted since the last sync
+pub fn get_logseq_uuids(
+    source: &dyn LogseqSource,
+    blocks: &str,
+) -> Result<HashSet<String>, String> {
+    let rows = source.query(&format!(
+        "[:find ?uuid :where {} [?b :block/uuid ?uuid]]",
+        blocks
+    ))?;
+    rows.as_array()
         .into_iter()
-        .map(|block| {
-            let title = block.title.unwrap_or_else(|| "Untitled".to_string());
-            let uuid = block.uuid.unwrap_or_default();
-            let tags = block
-                .tags
-                .unwrap_or_default()
+        .flatten()
+        .map(|row| match row.as_array().map(Vec::as_slice) {
+            Some([Value::String(uuid)]) => Ok(uuid.clone()),
+            _ => Err(format!("Unexpected query result: {}", row)),
+        })
+        .collect()
+}
+
+fn query_blocks(source: &dyn LogseqSource, datalog: &str) -> Result<Vec<LogseqBlock>, String> {
+    blocks_from_json(source.query(datalog)?)
+}
+
+/// Blocks of a query result, rows of a `:find` with a single pull are flattened
+pub fn blocks_from_json(value: Value) -> Result<Vec<LogseqBlock>, String> {
+    pulled(value)?.into_iter().map(block_from_json).collect()
+}
+
+/// Pulled blocks of a query result as JSON
+fn pulled(value: Value) -> Result<Vec<Value>, String> {
+    let rows = match value {
+        Value::Array(rows) => rows,
+        other => return Err(format!("Unexpected query result: {}", other)),
+    };
+    Ok(rows
+        .into_iter()
+        .flat_map(|row| match row {
+            Value::Array(columns) => columns,
+            block => vec![block],
+        })
+        .collect())
+}
+
+fn block_from_json(block: Value) -> Result<LogseqBlock, String> {
+    serde_json::from_value(block).map_err(|e| format!("Unexpected query result: {}", e))
+}
+
+/// Tags of a graph by their `db/id`, resolves the tag references of blocks
+#[derive(Debug, Default, PartialEq)]
+pub struct Tags(HashMap<i64, LogseqTag>);
+
+impl Tags {
+    pub fn from_blocks(blocks: Vec<LogseqBlock>) -> Tags {
+        Tags(
+            blocks
                 .into_iter()
-                .filter_map(|tag_ref| {
-                    tag_ref.id.and_then(|id| {
-                        tag_blocks
-                            .iter()
-                            .find(|tag_block| tag_block.id == Some(id))
-                            .map(|tag_block| LogseqTag {
-                                name: tag_block.title.clone().unwrap_or_default(),
-                                icon: tag_block.icon.clone(),
-                            })
-                    })
+                .filter_map(|block| {
+                    let tag = LogseqTag {
+                        name: block.title.unwrap_or_default(),
+                        icon: block.icon,
+                    };
+                    Some((block.id?, tag))
                 })
-                .collect();
+                .collect(),
+        )
+    }
 
-            LogseqPage {
-                title,
-                uuid,
-                tags,
-                updated_at: block.updated_at,
+    /// Tags of a block, references to blocks that aren't tags are skipped
+    pub fn resolve(&self, tag_refs: Option<Vec<TagRef>>) -> Vec<LogseqTag> {
+        tag_refs
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|tag_ref| self.0.get(&tag_ref.id?).cloned())
+            .collect()
+    }
+}
+
+/// Properties synced into pages, lowercased names by `db/ident`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Properties(BTreeMap<String, String>);
+
+impl Properties {
+    /// `{:ident [:block/title]}` patterns pulling the titles of the values
+    fn pull_patterns(&self) -> String {
+        self.0
+            .keys()
+            .map(|ident| format!(" {{:{} [:block/title]}}", ident))
+            .collect()
+    }
+
+    /// Values of the properties pulled into `block`. Several values of one
+    /// property are joined with `, `.
+    fn values(&self, block: &Value) -> BTreeMap<String, String> {
+        let title = |value: &Value| {
+            value
+                .get("block/title")
+                .or_else(|| value.get("title"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+
+        let mut values: BTreeMap<String, String> = BTreeMap::new();
+        for (ident, name) in &self.0 {
+            let titles: Vec<String> = match block
+                .get(ident)
+                .or_else(|| block.get(camel_case_name(ident)))
+            {
+                Some(Value::Array(items)) => items.iter().filter_map(title).collect(),
+                Some(value) => title(value).into_iter().collect(),
+                None => continue,
+            };
+            if titles.is_empty() {
+                continue;
             }
+            values
+                .entry(name.clone())
+                .and_modify(|v| {
+                    v.push_str(", ");
+                    v.push_str(&titles.join(", "));
+                })
+                .or_insert(titles.join(", "));
+        }
+        values
+    }
+}
+
+/// Name of an attribute in the results of the HTTP API, `user.property/due-date` is `dueDate`
+fn camel_case_name(ident: &str) -> String {
+    let name = ident.rsplit('/').next().unwrap_or(ident);
+    let mut words = name.split('-');
+    let first = words.next().unwrap_or_default().to_string();
+    words.fold(first, |mut camel, word| {
+        let mut chars = word.chars();
+        if let Some(c) = chars.next() {
+            camel.extend(c.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+        camel
+    })
+}
+
+/// Properties with the given names, their values are pulled with the pages
+pub fn get_logseq_properties(
+    source: &dyn LogseqSource,
+    names: &[String],
+) -> Result<Properties, String> {
+    if names.is_empty() {
+        return Ok(Properties::default());
+    }
+    // property titles are compared as written, built-in ones are capitalized
+    let names = names
+        .iter()
+        .flat_map(|n| {
+            let mut chars = n.chars();
+            let capitalized = chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default();
+            [n.clone(), n.to_lowercase(), capitalized]
+        })
+        .collect::<BTreeSet<_>>()
+        .iter()
+        .map(|n| format!("{:?}", n))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let rows = source.query(&format!(
+        "[:find ?ident ?name :where [?p :db/ident ?ident] [?p :block/title ?name] [(contains? #{{{}}} ?name)]]",
+        names
+    ))?;
+
+    let mut properties = BTreeMap::new();
+    for row in rows.as_array().into_iter().flatten() {
+        let [Value::String(ident), Value::String(name)] =
+            row.as_array().map(Vec::as_slice).unwrap_or_default()
+        else {
+            return Err(format!("Unexpected query result: {}", row));
+        };
+        properties.insert(
+            ident.trim_start_matches(':').to_string(),
+            name.to_lowercase(),
+        );
+    }
+    Ok(Properties(properties))
+}
+
+/// Pages with the values of `properties`, only the ones updated after `since` when given
+pub fn get_logseq_pages(
+    source: &dyn LogseqSource,
+    tags: &Tags,
+    properties: &Properties,
+    since: Option<i64>,
+) -> Result<Vec<LogseqPage>, String> {
+    let blocks = pulled(source.query(&format!(
+        "[:find (pull ?b [:block/tags :block/uuid :block/title :block/updated-at :block/journal-day {{:block/alias [:block/title]}}{}]) :where {}]",
+        properties.pull_patterns(),
+        where_clauses(PAGES, since)
+    ))?)?;
+
+    blocks
+        .into_iter()
+        .map(|json| {
+            let values = properties.values(&json);
+            let block = block_from_json(json)?;
+            Ok(LogseqPage {
+                graph: source.graph().to_string(),
+                title: block.title.unwrap_or_else(|| "Untitled".to_string()),
+                uuid: block.uuid.unwrap_or_default(),
+                tags: tags.resolve(block.tags),
+                aliases: block
+                    .aliases
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|a| a.title)
+                    .collect(),
+                properties: values,
+                updated_at: block.updated_at,
+                journal_day: block.journal_day,
+            })
+        })
+        .collect()
+}
+
+/// Blocks inside pages, with the title of the page they belong to. Only the
+/// ones updated after `since` when given.
+pub fn get_logseq_blocks(
+    source: &dyn LogseqSource,
+    tags: &Tags,
+    since: Option<i64>,
+) -> Result<Vec<LogseqBlockEntry>, String> {
+    let blocks = query_blocks(
+        source,
+        &format!(
+            "[:find (pull ?b [:block/tags :block/uuid :block/title :block/updated-at {{:block/page [:block/title]}}]) :where {}]",
+            where_clauses(BLOCKS, since)
+        ),
+    )?;
+
+    let entries = blocks
+        .into_iter()
+        .filter_map(|block| {
+            Some(LogseqBlockEntry {
+                graph: source.graph().to_string(),
+                content: block.title?,
+                uuid: block.uuid?,
+                page: block.page.and_then(|p| p.title).unwrap_or_default(),
+                tags: tags.resolve(block.tags),
+                updated_at: block.updated_at,
+            })
         })
         .collect();
 
-    Ok(pages)
+    Ok(entries)
 }
 
-pub fn get_logseq_tag_blocks() -> Result<Vec<LogseqBlock>, String> {
-    let output = Command::new("bash")
-        .arg("-c")
-        .arg("npx @logseq/cli query illef '[:find (pull ?b [:db/id :block/title :logseq.property/icon]) :where [?tag :db/ident :logseq.class/Tag] [?b :block/tags ?tag]]' | jet --to json")
-        .output()
-        .map_err(|e| format!("Failed to execute command: {}", e))?;
+/// Blocks tagged with `#Task`, with their status, priority and dates. Only
+/// the ones updated after `since` when given.
+pub fn get_logseq_tasks(
+    source: &dyn LogseqSource,
+    tags: &Tags,
+    since: Option<i64>,
+) -> Result<Vec<LogseqTaskEntry>, String> {
+    let blocks = query_blocks(
+        source,
+        &format!(
+            "[:find (pull ?b [:block/tags :block/uuid :block/title :block/updated-at {{:block/page [:block/title]}} {{:logseq.property/status [:block/title]}} {{:logseq.property/priority [:block/title]}} :logseq.property/scheduled :logseq.property/deadline]) :where {}]",
+            where_clauses(TASKS, since)
+        ),
+    )?;
+    let title =
+        |block: Option<Box<LogseqBlock>>| block.and_then(|b| b.title).map(|t| t.to_lowercase());
 
-    if !output.status.success() {
-        return Err(format!(
-            "Command failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+    let tasks = blocks
+        .into_iter()
+        .filter_map(|block| {
+            Some(LogseqTaskEntry {
+                graph: source.graph().to_string(),
+                content: block.title?,
+                uuid: block.uuid?,
+                page: block.page.and_then(|p| p.title).unwrap_or_default(),
+                status: title(block.status),
+                priority: title(block.priority),
+                scheduled: block.scheduled,
+                deadline: block.deadline,
+                tags: tags.resolve(block.tags),
+                updated_at: block.updated_at,
+            })
+        })
+        .collect();
+
+    Ok(tasks)
+}
+
+/// Tags of the graph, with their icons
+pub fn get_logseq_tags(source: &dyn LogseqSource) -> Result<Tags, String> {
+    query_blocks(
+        source,
+        "[:find (pull ?b [:db/id :block/title :logseq.property/icon]) :where [?tag :db/ident :logseq.class/Tag] [?b :block/tags ?tag]]",
+    )
+    .map(Tags::from_blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::RefCell;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!(
+            "{}/tests/fixtures/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+        .unwrap()
     }
 
-    let json_str =
-        String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 in output: {}", e))?;
+    fn parse_blocks(edn_str: &str) -> Result<Vec<LogseqBlock>, String> {
+        blocks_from_json(parse_edn(edn_str)?)
+    }
+
+    /// Answers queries with the fixture whose key the datalog contains
+    struct FixtureSource(Vec<(&'static str, &'static str)>);
+
+    impl LogseqSource for FixtureSource {
+        fn graph(&self) -> &str {
+            "illef"
+        }
+
+        fn query(&self, datalog: &str) -> Result<Value, String> {
+            let (_, name) = self
+                .0
+                .iter()
+                .find(|(key, _)| datalog.contains(key))
+                .ok_or_else(|| format!("Unexpected query {}", datalog))?;
+            parse_edn(&fixture(name))
+        }
+    }
+
+    #[test]
+    fn test_command() {
+        let cli = LogseqCli {
+            graph: "it's mine".to_string(),
+            ..LogseqCli::default()
+        };
+        let command = cli.command("[:find ?b]").unwrap();
+
+        assert_eq!(command.get_program(), "npx");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec!["@logseq/cli", "query", "it's mine", "[:find ?b]"]
+        );
+        let quoted = LogseqCli {
+            command: "'/opt/Logseq CLI/logseq' --verbose".to_string(),
+            ..cli.clone()
+        }
+        .command("[:find ?b]")
+        .unwrap();
+        assert_eq!(quoted.get_program(), "/opt/Logseq CLI/logseq");
+        assert_eq!(quoted.get_args().next().unwrap(), "--verbose");
+        assert!(LogseqCli {
+            command: " ".to_string(),
+            ..cli.clone()
+        }
+        .command("[:find ?b]")
+        .is_err());
+        assert!(LogseqCli {
+            command: "'npx".to_string(),
+            ..cli
+        }
+        .command("[:find ?b]")
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_pages() {
+        let blocks = parse_blocks(&fixture("pages.edn")).unwrap();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].title.as_deref(), Some("'될놈될'은 진실인가?"));
+        assert_eq!(
+            blocks[0].uuid.as_deref(),
+            Some("67c56741-df35-408b-a4d8-df08ebcd030e")
+        );
+        assert_eq!(blocks[0].updated_at, Some(1741006657000));
+        assert_eq!(
+            blocks[1]
+                .tags
+                .iter()
+                .flatten()
+                .map(|t| t.id)
+                .collect::<Vec<_>>(),
+            vec![Some(136), Some(212)]
+        );
+        assert_eq!(blocks[2].updated_at, None);
+
+        let journals =
+            parse_blocks("[{:block/title \"Oct 19th, 2026\" :block/journal-day 20261019}]")
+                .unwrap();
+        assert_eq!(journals[0].journal_day, Some(20261019));
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let blocks = parse_blocks(&fixture("tags.edn")).unwrap();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            blocks[0].icon,
+            Some(LogseqIcon {
+                icon_type: "emoji".to_string(),
+                id: "books".to_string(),
+                color: None,
+            })
+        );
+        assert_eq!(blocks[1].icon.as_ref().unwrap().icon_type, "tabler-icon");
+        assert_eq!(blocks[2].id, Some(8219));
+        assert!(parse_blocks("[{:db/id 1}").is_err());
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let tags = Tags::from_blocks(parse_blocks(&fixture("tags.edn")).unwrap());
+        let blocks = parse_blocks(&fixture("blocks.edn")).unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        let page = blocks[0].page.as_ref().unwrap();
+        assert_eq!(page.title.as_deref(), Some("삶"));
+        assert_eq!(
+            tags.resolve(blocks[0].tags.clone())
+                .into_iter()
+                .map(|t| t.name)
+                .collect::<Vec<_>>(),
+            vec!["Book"]
+        );
+        assert!(blocks[1].page.is_none());
+    }
 
-    let blocks: Vec<LogseqBlock> =
-        serde_json::from_str(&json_str).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    #[test]
+    fn test_get_logseq_pages() {
+        let source = FixtureSource(vec![
+            (":logseq.class/Tag", "tags.edn"),
+            (":block/alias", "pages.edn"),
+            ("?ident ?name", "properties.edn"),
+        ]);
+        let tags = get_logseq_tags(&source).unwrap();
+        let properties =
+            get_logseq_properties(&source, &["status".to_string(), "type".to_string()]).unwrap();
+        let pages = get_logseq_pages(&source, &tags, &properties, None).unwrap();
 
-    Ok(blocks)
+        assert_eq!(pages[1].title, "삶");
+        assert_eq!(pages[1].aliases, vec!["life", "인생"]);
+        assert_eq!(pages[0].tags[0].name, "Book");
+        assert_eq!(pages[1].properties["status"], "Doing");
+        assert_eq!(pages[1].properties["type"], "essay, note");
+        assert_eq!(pages[0].properties["type"], "question");
+        assert!(pages[2].properties.is_empty());
+        assert_eq!(
+            get_logseq_properties(&source, &[]).unwrap(),
+            Properties::default()
+        );
+    }
+
+    #[test]
+    fn test_property_values_of_api_blocks() {
+        let properties =
+            Properties([("user.property/due-date-x1".to_string(), "due".to_string())].into());
+        let block = json!({"title": "삶", "dueDateX1": {"title": "Oct 21st, 2026"}});
+
+        assert_eq!(properties.values(&block)["due"], "Oct 21st, 2026");
+        assert!(properties
+            .pull_patterns()
+            .contains("{:user.property/due-date-x1 [:block/title]}"));
+    }
+
+    /// Answers every query with `edn`, recording the queries
+    struct EdnSource {
+        edn: &'static str,
+        queries: RefCell<Vec<String>>,
+    }
+
+    impl LogseqSource for EdnSource {
+        fn graph(&self) -> &str {
+            "illef"
+        }
+
+        fn query(&self, datalog: &str) -> Result<Value, String> {
+            self.queries.borrow_mut().push(datalog.to_string());
+            parse_edn(self.edn)
+        }
+    }
+
+    #[test]
+    fn test_incremental_queries() {
+        let source = EdnSource {
+            edn: "[[#uuid \"67c56741-df35-408b-a4d8-df08ebcd030e\"] [#uuid \"6821bf0d-3ac7-42cc-a5ba-5b742b809633\"]]",
+            queries: RefCell::new(vec![]),
+        };
+
+        let uuids = get_logseq_uuids(&source, PAGES).unwrap();
+        assert_eq!(uuids.len(), 2);
+        assert!(uuids.contains("6821bf0d-3ac7-42cc-a5ba-5b742b809633"));
+
+        let source = EdnSource {
+            edn: "[]",
+            queries: RefCell::new(vec![]),
+        };
+        get_logseq_pages(
+            &source,
+            &Tags::default(),
+            &Properties::default(),
+            Some(1760000000000),
+        )
+        .unwrap();
+        get_logseq_blocks(&source, &Tags::default(), None).unwrap();
+        let queries = source.queries.borrow();
+        assert!(queries[0]
+            .ends_with("[?b :block/updated-at ?updated-at] [(> ?updated-at 1760000000000)]]"));
+        assert!(!queries[1].contains(":block/updated-at ?updated-at"));
+    }
+
+    #[test]
+    fn test_get_logseq_tasks() {
+        let source = FixtureSource(vec![
+            (":logseq.class/Tag", "tags.edn"),
+            (":logseq.class/Task", "tasks.edn"),
+        ]);
+        let tags = get_logseq_tags(&source).unwrap();
+        let tasks = get_logseq_tasks(&source, &tags, None).unwrap();
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].status.as_deref(), Some("doing"));
+        assert_eq!(tasks[0].priority.as_deref(), Some("high"));
+        assert_eq!(tasks[0].deadline, Some(1761055200000));
+        assert_eq!(tasks[0].page, "Oct 19th, 2026");
+        assert_eq!(tasks[1].priority, None);
+        assert_eq!(tasks[1].scheduled, Some(1760972400000));
+        assert_eq!(tasks[1].tags[0].name, "Book");
+    }
+
+    /// Graph with `pages` pages tagged with 5 of `tags` tags each
+    struct SyntheticGraph {
+        tags: Value,
+        pages: Value,
+    }
+
+    impl SyntheticGraph {
+        fn new(tags: i64, pages: i64) -> Self {
+            let tag_blocks = (0..tags)
+                .map(|id| json!({"db/id": id, "block/title": format!("tag {}", id)}))
+                .collect();
+            let page_blocks = (0..pages)
+                .map(|i| {
+                    json!([{
+                        "block/uuid": format!("page-{}", i),
+                        "block/title": format!("page {}", i),
+                        "block/updated-at": i,
+                        "block/tags": (0..5).map(|t| json!({"db/id": (i * 7 + t * 13) % tags})).collect::<Vec<_>>(),
+                    }])
+                })
+                .collect();
+            SyntheticGraph {
+                tags: Value::Array(tag_blocks),
+                pages: Value::Array(page_blocks),
+            }
+        }
+    }
+
+    impl LogseqSource for SyntheticGraph {
+        fn graph(&self) -> &str {
+            "synthetic"
+        }
+
+        fn query(&self, datalog: &str) -> Result<Value, String> {
+            if datalog.contains(":logseq.class/Tag") {
+                Ok(self.tags.clone())
+            } else {
+                Ok(self.pages.clone())
+            }
+        }
+    }
+
+    /// Run with `cargo test --release -p logseq -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_get_logseq_pages_large_graph() {
+        let graph = SyntheticGraph::new(2_000, 50_000);
+
+        let started = std::time::Instant::now();
+        let tags = get_logseq_tags(&graph).unwrap();
+        let pages = get_logseq_pages(&graph, &tags, &Properties::default(), None).unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(pages.len(), 50_000);
+        assert!(pages.iter().all(|p| p.tags.len() == 5));
+        println!(
+            "get_logseq_pages: {} pages with {} tags in {:?}",
+            pages.len(),
+            tags.0.len(),
+            elapsed
+        );
+    }
 }
@@ -0,0 +1,251 @@
+use chrono::{Days, NaiveDate};
+use std::collections::BTreeMap;
+
+/// `due:` filter of the task view, tasks are due on their deadline or
+/// scheduled day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Due {
+    Overdue,
+    Today,
+    Week,
+    Month,
+}
+
+impl Due {
+    fn parse(value: &str) -> Option<Due> {
+        match value.to_lowercase().as_str() {
+            "overdue" => Some(Due::Overdue),
+            "today" => Some(Due::Today),
+            "week" => Some(Due::Week),
+            "month" => Some(Due::Month),
+            _ => None,
+        }
+    }
+
+    /// Whether a task due on `date` is due within the period, overdue tasks
+    /// are due in every period
+    pub fn includes(&self, date: NaiveDate, today: NaiveDate) -> bool {
+        let days = match self {
+            Due::Overdue => return date < today,
+            Due::Today => 0,
+            Due::Week => 6,
+            Due::Month => 30,
+        };
+        today
+            .checked_add_days(Days::new(days))
+            .is_some_and(|end| date <= end)
+    }
+}
+
+/// Properties of tasks, filterable with `name:value` in the task view
+const TASK_PROPERTIES: [&str; 2] = ["status", "priority"];
+
+/// Parsed launcher query
+#[derive(Debug, Default, PartialEq)]
+pub struct Query {
+    /// Only search the graph given with `graph:<name>`
+    pub graph: Option<String>,
+    /// `name:value` filters of the configured properties, both lowercased
+    pub properties: Vec<(String, String)>,
+    /// Lowercased search text without the filter tokens
+    pub text: String,
+    /// Search text as typed, used as the title of a new page
+    pub input: String,
+    /// Search blocks instead of pages, the query starts with `>`
+    pub blocks: bool,
+    /// Block to add to today's journal, the query starts with `+`. A
+    /// `graph:<name>` in it is the graph the block is meant for.
+    pub capture: Option<String>,
+    /// Search tasks instead of pages, the query starts with `task:`
+    pub tasks: bool,
+    /// Only show tasks due in the `due:<period>` period
+    pub due: Option<Due>,
+}
+
+impl Query {
+    /// Parses the query, `name:value` is only a filter when `name` is one of
+    /// `property_names` or a task property in the task view. Other words with
+    /// a colon, like `Re:Zero`, are searched as they are.
+    pub fn parse(query: &str, property_names: &[String]) -> Query {
+        let query = query.trim_start();
+        if let Some(capture) = query.strip_prefix('+') {
+            let (graphs, words): (Vec<&str>, Vec<&str>) = capture
+                .split_whitespace()
+                .partition(|w| w.strip_prefix("graph:").is_some_and(|g| !g.is_empty()));
+            return Query {
+                graph: graphs.last().map(|g| g["graph:".len()..].to_lowercase()),
+                capture: Some(words.join(" ")),
+                ..Query::default()
+            };
+        }
+        let (query, blocks) = match query.strip_prefix('>') {
+            Some(rest) => (rest, true),
+            None => (query, false),
+        };
+        let (query, tasks) = match query.strip_prefix("task:") {
+            Some(rest) => (rest, true),
+            None => (query, false),
+        };
+        let mut graph = None;
+        let mut due = None;
+        let mut words = vec![];
+
+        let mut properties = vec![];
+        let is_property = |name: &str| {
+            property_names.iter().any(|p| p.eq_ignore_ascii_case(name))
+                || (tasks && TASK_PROPERTIES.iter().any(|p| p.eq_ignore_ascii_case(name)))
+        };
+
+        for word in query.split_whitespace() {
+            match word.split_once(':') {
+                Some(("graph", name)) if !name.is_empty() => graph = Some(name.to_lowercase()),
+                Some(("due", period)) if tasks && Due::parse(period).is_some() => {
+                    due = Due::parse(period)
+                }
+                Some((name, value)) if !value.is_empty() && is_property(name) => {
+                    properties.push((name.to_lowercase(), value.to_lowercase()))
+                }
+                _ => words.push(word),
+            }
+        }
+
+        let input = words.join(" ");
+        Query {
+            graph,
+            properties,
+            text: input.to_lowercase(),
+            input,
+            blocks,
+            capture: None,
+            tasks,
+            due,
+        }
+    }
+
+    /// Whether all words of the search text appear in `text`
+    pub fn matches_words(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.text.split_whitespace().all(|w| text.contains(w))
+    }
+
+    /// Whether every property filter is contained in the value of its property
+    pub fn matches_properties(&self, properties: &BTreeMap<String, String>) -> bool {
+        self.properties.iter().all(|(name, value)| {
+            properties
+                .get(name)
+                .is_some_and(|v| v.to_lowercase().contains(value))
+        })
+    }
+
+    pub fn matches_graph(&self, graph: &str) -> bool {
+        self.graph
+            .as_ref()
+            .is_none_or(|g| graph.to_lowercase() == *g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses with the default `status,type` properties
+    fn parse(query: &str) -> Query {
+        Query::parse(query, &["status".to_string(), "type".to_string()])
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse("Rust  graph:Work notes"),
+            Query {
+                graph: Some("work".to_string()),
+                properties: vec![],
+                text: "rust notes".to_string(),
+                input: "Rust notes".to_string(),
+                blocks: false,
+                capture: None,
+                tasks: false,
+                due: None,
+            }
+        );
+        assert_eq!(parse("graph:").text, "graph:");
+
+        let query = parse("graph:work");
+        assert!(query.matches_graph("Work"));
+        assert!(!query.matches_graph("personal"));
+        assert!(parse("rust").matches_graph("personal"));
+    }
+
+    #[test]
+    fn test_parse_block_query() {
+        let query = parse(" >Book  graph:work");
+        assert!(query.blocks);
+        assert_eq!(query.text, "book");
+        assert!(query.matches_words("Lent books to a friend"));
+        assert!(!parse("> book friend").matches_words("Lent books"));
+        assert!(!parse("book > x").blocks);
+    }
+
+    #[test]
+    fn test_parse_properties() {
+        let query = parse("essay Status:Doing 10:30 type: http://x rust:async");
+        assert_eq!(
+            query.properties,
+            vec![("status".to_string(), "doing".to_string())]
+        );
+        assert_eq!(query.text, "essay 10:30 type: http://x rust:async");
+        assert!(parse("priority:high").properties.is_empty());
+
+        let properties = BTreeMap::from([("status".to_string(), "Doing".to_string())]);
+        assert!(query.matches_properties(&properties));
+        assert!(parse("essay").matches_properties(&properties));
+        assert!(!parse("status:done").matches_properties(&properties));
+        assert!(!parse("type:note").matches_properties(&properties));
+    }
+
+    #[test]
+    fn test_colon_words_are_searched() {
+        let query = parse("Re:Zero todo:later");
+        assert!(query.properties.is_empty());
+        assert_eq!(query.text, "re:zero todo:later");
+        assert_eq!(query.input, "Re:Zero todo:later");
+        assert!(parse("Re:Zero").matches_words("Re:Zero − Starting Life in Another World"));
+    }
+
+    #[test]
+    fn test_parse_task_query() {
+        let query = parse("task: report status:todo due:week");
+        assert!(query.tasks);
+        assert_eq!(query.text, "report");
+        assert_eq!(
+            query.properties,
+            vec![("status".to_string(), "todo".to_string())]
+        );
+        assert_eq!(query.due, Some(Due::Week));
+        assert_eq!(
+            parse("task: priority:high").properties,
+            vec![("priority".to_string(), "high".to_string())]
+        );
+        assert_eq!(parse("task: due:someday").due, None);
+        assert_eq!(parse("due:week").due, None);
+
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let today = date(19);
+        assert!(Due::Overdue.includes(date(18), today));
+        assert!(!Due::Overdue.includes(today, today));
+        assert!(Due::Today.includes(date(1), today));
+        assert!(!Due::Today.includes(date(20), today));
+        assert!(Due::Week.includes(date(25), today));
+        assert!(!Due::Week.includes(date(26), today));
+        assert!(Due::Month.includes(date(26), today));
+    }
+
+    #[test]
+    fn test_parse_capture() {
+        let query = parse(" + Call  Bob #inbox graph:Work");
+        assert_eq!(query.capture.as_deref(), Some("Call Bob #inbox"));
+        assert_eq!(query.graph.as_deref(), Some("work"));
+        assert_eq!(parse("+ graph: x").capture.as_deref(), Some("graph: x"));
+        assert_eq!(parse("Call + Bob").capture, None);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{cache::LogseqPage, usage::ItemUsage};
+use std::{cmp::Reverse, collections::HashMap};
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// How well `text` matches `candidate`, both lowercased
+fn text_score(candidate: &str, text: &str) -> u32 {
+    if candidate == text {
+        100
+    } else if candidate.starts_with(text) {
+        80
+    } else if candidate
+        .match_indices(text)
+        .any(|(i, _)| !candidate[..i].ends_with(char::is_alphanumeric))
+    {
+        // the text starts a word of the candidate
+        60
+    } else if candidate.contains(text) {
+        40
+    } else {
+        0
+    }
+}
+
+/// How well the page matches the search text, `None` if it doesn't match
+pub fn match_score(page: &LogseqPage, text: &str) -> Option<u32> {
+    if text.is_empty() {
+        return Some(0);
+    }
+
+    let title = text_score(&page.title.to_lowercase(), text);
+    // aliases and tags count a bit less than the title itself
+    let alias = page
+        .aliases
+        .iter()
+        .map(|a| text_score(&a.to_lowercase(), text) * 9 / 10)
+        .max()
+        .unwrap_or(0);
+    let tag = page
+        .tags
+        .iter()
+        .map(|t| text_score(&t.name.to_lowercase(), text) / 2)
+        .max()
+        .unwrap_or(0);
+
+    Some(title.max(alias).max(tag)).filter(|score| *score > 0)
+}
+
+/// Boost for recently updated pages
+pub fn recency_score(updated_at: Option<i64>, now_millis: i64) -> u32 {
+    let Some(updated_at) = updated_at else {
+        return 0;
+    };
+    match (now_millis - updated_at) / DAY_MILLIS {
+        ..1 => 30,
+        1..7 => 20,
+        7..30 => 10,
+        30..365 => 5,
+        _ => 0,
+    }
+}
+
+/// Boost for frequently opened pages
+pub fn frequency_score(usage: Option<&ItemUsage>, now_millis: i64) -> u32 {
+    usage
+        .map(|u| (u.frecency((now_millis / 1000).max(0) as u64) / 10).min(50))
+        .unwrap_or(0)
+}
+
+/// Key the opens of a page are recorded with
+pub fn usage_key(graph: &str, uuid: &str, title: &str) -> String {
+    if uuid.is_empty() {
+        format!("{}/{}", graph, title)
+    } else {
+        format!("{}/{}", graph, uuid)
+    }
+}
+
+/// Pages matching `text`, best match first. Pages scoring the same keep the
+/// most recently updated first, then sort by title, so the order is stable.
+pub fn rank_pages(
+    pages: Vec<LogseqPage>,
+    text: &str,
+    usage: &HashMap<String, ItemUsage>,
+    now_millis: i64,
+) -> Vec<LogseqPage> {
+    let mut scored: Vec<(u32, LogseqPage)> = pages
+        .into_iter()
+        .filter_map(|page| {
+            let usage = usage.get(&usage_key(&page.graph, &page.uuid, &page.title));
+            let score = match_score(&page, text)?
+                + recency_score(page.updated_at, now_millis)
+                + frequency_score(usage, now_millis);
+            Some((score, page))
+        })
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        (Reverse(a_score), Reverse(a.updated_at), &a.title, &a.graph).cmp(&(
+            Reverse(b_score),
+            Reverse(b.updated_at),
+            &b.title,
+            &b.graph,
+        ))
+    });
+    scored.into_iter().map(|(_, page)| page).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cache::LogseqTag, query::Query};
+
+    const NOW: i64 = 1_760_000_000_000;
+
+    fn page(title: &str, updated_days_ago: i64) -> LogseqPage {
+        LogseqPage {
+            graph: "illef".to_string(),
+            title: title.to_string(),
+            uuid: title.to_lowercase(),
+            updated_at: Some(NOW - updated_days_ago * DAY_MILLIS),
+            ..Default::default()
+        }
+    }
+
+    fn titles(pages: &[LogseqPage]) -> Vec<&str> {
+        pages.iter().map(|p| p.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_text_score() {
+        assert_eq!(text_score("rust", "rust"), 100);
+        assert_eq!(text_score("rustacean", "rust"), 80);
+        assert_eq!(text_score("learning rust", "rust"), 60);
+        assert_eq!(text_score("trust", "rust"), 40);
+        assert_eq!(text_score("go", "rust"), 0);
+    }
+
+    #[test]
+    fn test_rank_pages() {
+        let mut tagged = page("Ownership", 400);
+        tagged.tags.push(LogseqTag {
+            name: "Rust".to_string(),
+            icon: None,
+        });
+        let pages = vec![
+            page("Trust issues", 0),
+            tagged,
+            page("Learning Rust", 3),
+            page("Rust", 400),
+            page("Go", 0),
+        ];
+
+        let ranked = rank_pages(pages.clone(), "rust", &HashMap::new(), NOW);
+        assert_eq!(
+            titles(&ranked),
+            vec!["Rust", "Learning Rust", "Trust issues", "Ownership"]
+        );
+        // ranking doesn't change between calls
+        assert_eq!(
+            titles(&rank_pages(pages.clone(), "rust", &HashMap::new(), NOW)),
+            titles(&ranked)
+        );
+
+        let usage = HashMap::from([(
+            "illef/trust issues".to_string(),
+            ItemUsage {
+                key: "illef/trust issues".to_string(),
+                count: 5,
+                last_accessed_time: (NOW / 1000) as u64,
+            },
+        )]);
+        assert_eq!(
+            titles(&rank_pages(pages, "rust", &usage, NOW))[..2],
+            ["Trust issues", "Rust"]
+        );
+    }
+
+    #[test]
+    fn test_rank_pages_with_colon() {
+        let query = Query::parse("Re:Zero", &["status".to_string(), "type".to_string()]);
+        let pages = vec![page("Re:Zero", 10), page("Zero", 0)];
+
+        assert!(query.matches_properties(&pages[0].properties));
+        assert_eq!(
+            titles(&rank_pages(pages, &query.text, &HashMap::new(), NOW)),
+            vec!["Re:Zero"]
+        );
+    }
+
+    #[test]
+    fn test_rank_pages_without_text() {
+        let pages = vec![page("b", 40), page("a", 40), page("c", 0)];
+        assert_eq!(
+            titles(&rank_pages(pages, "", &HashMap::new(), NOW)),
+            vec!["c", "a", "b"]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use crate::cache::{graph_file, CacheError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ureq::serde_json;
+
+/// Syncs ending like the written status are only written this often,
+/// in milliseconds. The stored `last_success` may be this much behind.
+pub const WRITE_INTERVAL: i64 = 5 * 60 * 1000;
+
+/// Outcome of the syncs of a graph, times are milliseconds since epoch
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SyncStatus {
+    pub last_success: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<i64>,
+    /// Last sync skipped because the graph wasn't open in logseq
+    pub last_skipped: Option<i64>,
+    /// Pages in the cache after the last successful sync
+    pub page_count: usize,
+    /// The cache is stale when it wasn't synced for this long, `None` for
+    /// graphs only synced when they change
+    pub stale_after: Option<i64>,
+}
+
+/// How the latest sync of a graph ended
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    NotSynced,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+impl SyncStatus {
+    fn outcome(&self) -> Outcome {
+        let latest = self
+            .last_success
+            .max(self.last_error_at)
+            .max(self.last_skipped);
+        match latest {
+            None => Outcome::NotSynced,
+            _ if latest == self.last_skipped => Outcome::Skipped,
+            _ if latest == self.last_error_at => Outcome::Failed,
+            _ => Outcome::Succeeded,
+        }
+    }
+
+    /// Whether `self` tells more than the `written` status, syncs ending like
+    /// the written one are only worth writing every `WRITE_INTERVAL`
+    fn differs_from(&self, written: &SyncStatus, now_millis: i64) -> bool {
+        let written_at = written
+            .last_success
+            .max(written.last_error_at)
+            .max(written.last_skipped);
+        self.outcome() != written.outcome()
+            || self.last_error != written.last_error
+            || self.page_count != written.page_count
+            || self.stale_after != written.stale_after
+            || written_at.is_none_or(|at| now_millis - at >= WRITE_INTERVAL)
+    }
+
+    /// Why the cached pages may be missing or outdated, `None` when the last
+    /// sync succeeded recently or was skipped
+    pub fn problem(&self, graph: &str, now_millis: i64) -> Option<String> {
+        // graphs read through the API are only synced while open in logseq
+        if self.last_skipped > self.last_success.max(self.last_error_at) {
+            return None;
+        }
+        let Some(last_success) = self.last_success else {
+            return Some(match self.last_error {
+                Some(_) => format!("Logseq graph {} failed to sync", graph),
+                None => format!("Logseq graph {} is not synced yet", graph),
+            });
+        };
+        if self.last_error_at.is_some_and(|at| at > last_success) {
+            return Some(format!("Logseq graph {} failed to sync", graph));
+        }
+        if self
+            .stale_after
+            .is_some_and(|stale_after| now_millis - last_success > stale_after)
+        {
+            let minutes = (now_millis - last_success) / 60_000;
+            return Some(format!(
+                "Logseq graph {} was last synced {} minutes ago",
+                graph, minutes
+            ));
+        }
+        if self.page_count == 0 {
+            return Some(format!("Logseq graph {} has no pages", graph));
+        }
+        None
+    }
+}
+
+/// Sync status of a graph, stored next to its caches. The status is kept in
+/// memory and only written when the outcome of the syncs changes.
+pub struct FileSyncStatus {
+    file_name: PathBuf,
+    /// Status after the last sync, loaded on the first one
+    status: Option<SyncStatus>,
+    /// Status in the file
+    written: Option<SyncStatus>,
+}
+
+impl FileSyncStatus {
+    pub fn for_graph(graph: &str) -> FileSyncStatus {
+        FileSyncStatus::new(graph_file(graph, "status.json"))
+    }
+
+    pub fn new<P: AsRef<Path>>(file_name: P) -> Self {
+        FileSyncStatus {
+            file_name: file_name.as_ref().to_path_buf(),
+            status: None,
+            written: None,
+        }
+    }
+
+    /// The stored status, the default one when the graph was never synced
+    pub fn load(&self) -> SyncStatus {
+        std::fs::read_to_string(&self.file_name)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, status: &SyncStatus) -> Result<(), CacheError> {
+        if let Some(parent_dir) = self.file_name.parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+        std::fs::write(&self.file_name, serde_json::to_string(status)?)?;
+        Ok(())
+    }
+
+    /// Records the outcome of a sync, `page_count` is only used when it succeeded
+    pub fn record(
+        &mut self,
+        result: &Result<usize, String>,
+        stale_after: Option<i64>,
+        now_millis: i64,
+    ) -> Result<(), CacheError> {
+        self.update(now_millis, |status| {
+            status.stale_after = stale_after;
+            match result {
+                Ok(page_count) => {
+                    status.last_success = Some(now_millis);
+                    status.page_count = *page_count;
+                }
+                Err(e) => {
+                    status.last_error = Some(e.clone());
+                    status.last_error_at = Some(now_millis);
+                }
+            }
+        })
+    }
+
+    /// Records a sync skipped because the graph isn't open in logseq
+    pub fn record_skipped(&mut self, now_millis: i64) -> Result<(), CacheError> {
+        self.update(now_millis, |status| status.last_skipped = Some(now_millis))
+    }
+
+    fn update<F: FnOnce(&mut SyncStatus)>(
+        &mut self,
+        now_millis: i64,
+        f: F,
+    ) -> Result<(), CacheError> {
+        if self.status.is_none() {
+            let loaded = self.load();
+            self.written = Some(loaded.clone());
+            self.status = Some(loaded);
+        }
+        let status = self.status.get_or_insert_default();
+        f(status);
+
+        if self
+            .written
+            .as_ref()
+            .is_none_or(|written| status.differs_from(written, now_millis))
+        {
+            let status = status.clone();
+            self.save(&status)?;
+            self.written = Some(status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+    const NOW: i64 = 1_760_000_000_000;
+
+    #[test]
+    fn test_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = FileSyncStatus::new(dir.path().join("logseq.illef.status.json"));
+        assert_eq!(
+            file.load().problem("illef", NOW).unwrap(),
+            "Logseq graph illef is not synced yet"
+        );
+
+        file.record(&Ok(42), Some(10 * MINUTE), NOW).unwrap();
+        file.record(
+            &Err("Command failed".to_string()),
+            Some(10 * MINUTE),
+            NOW + MINUTE,
+        )
+        .unwrap();
+        let status = file.load();
+        assert_eq!(status.page_count, 42);
+        assert_eq!(status.last_success, Some(NOW));
+        assert_eq!(status.last_error.as_deref(), Some("Command failed"));
+        assert_eq!(
+            status.problem("illef", NOW + MINUTE).unwrap(),
+            "Logseq graph illef failed to sync"
+        );
+
+        file.record(&Ok(43), Some(10 * MINUTE), NOW + 2 * MINUTE)
+            .unwrap();
+        assert_eq!(file.load().problem("illef", NOW + 3 * MINUTE), None);
+    }
+
+    #[test]
+    fn test_record_writes_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logseq.illef.status.json");
+        let stored = || FileSyncStatus::new(&path).load();
+        let mut file = FileSyncStatus::new(&path);
+
+        file.record(&Ok(42), Some(10 * MINUTE), NOW).unwrap();
+        file.record(&Ok(42), Some(10 * MINUTE), NOW + MINUTE)
+            .unwrap();
+        assert_eq!(stored().last_success, Some(NOW));
+        file.record(&Ok(43), Some(10 * MINUTE), NOW + 2 * MINUTE)
+            .unwrap();
+        assert_eq!(stored().last_success, Some(NOW + 2 * MINUTE));
+
+        let failed = |e: &str| Err(e.to_string());
+        file.record(
+            &failed("Command failed"),
+            Some(10 * MINUTE),
+            NOW + 3 * MINUTE,
+        )
+        .unwrap();
+        file.record(
+            &failed("Command failed"),
+            Some(10 * MINUTE),
+            NOW + 4 * MINUTE,
+        )
+        .unwrap();
+        assert_eq!(stored().last_error_at, Some(NOW + 3 * MINUTE));
+        file.record(&failed("Timed out"), Some(10 * MINUTE), NOW + 5 * MINUTE)
+            .unwrap();
+        assert_eq!(stored().last_error.as_deref(), Some("Timed out"));
+
+        file.record(&Ok(43), Some(10 * MINUTE), NOW + 6 * MINUTE)
+            .unwrap();
+        file.record(&Ok(43), Some(10 * MINUTE), NOW + 7 * MINUTE)
+            .unwrap();
+        assert_eq!(stored().last_success, Some(NOW + 6 * MINUTE));
+        file.record(
+            &Ok(43),
+            Some(10 * MINUTE),
+            NOW + 6 * MINUTE + WRITE_INTERVAL,
+        )
+        .unwrap();
+        assert_eq!(
+            stored().last_success,
+            Some(NOW + 6 * MINUTE + WRITE_INTERVAL)
+        );
+    }
+
+    #[test]
+    fn test_record_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = FileSyncStatus::new(dir.path().join("logseq.work.status.json"));
+
+        file.record_skipped(NOW).unwrap();
+        assert_eq!(file.load().problem("work", NOW + 90 * MINUTE), None);
+
+        file.record(&Ok(3), Some(10 * MINUTE), NOW + MINUTE)
+            .unwrap();
+        file.record_skipped(NOW + 2 * MINUTE).unwrap();
+        let status = file.load();
+        assert_eq!(status.last_error_at, None);
+        assert_eq!(status.problem("work", NOW + 90 * MINUTE), None);
+
+        file.record(
+            &Err("Command failed".to_string()),
+            Some(10 * MINUTE),
+            NOW + 3 * MINUTE,
+        )
+        .unwrap();
+        assert_eq!(
+            file.load().problem("work", NOW + 3 * MINUTE).unwrap(),
+            "Logseq graph work failed to sync"
+        );
+    }
+
+    #[test]
+    fn test_problem() {
+        let status = SyncStatus {
+            last_success: Some(NOW),
+            page_count: 3,
+            stale_after: Some(10 * MINUTE),
+            ..SyncStatus::default()
+        };
+        assert_eq!(
+            status.problem("illef", NOW + 90 * MINUTE).unwrap(),
+            "Logseq graph illef was last synced 90 minutes ago"
+        );
+        // watched graphs are only synced when they change
+        let watched = SyncStatus {
+            stale_after: None,
+            ..status.clone()
+        };
+        assert_eq!(watched.problem("notes", NOW + 90 * MINUTE), None);
+        assert_eq!(
+            SyncStatus {
+                page_count: 0,
+                ..status
+            }
+            .problem("illef", NOW)
+            .unwrap(),
+            "Logseq graph illef has no pages"
+        );
+    }
+}
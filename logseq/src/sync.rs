@@ -0,0 +1,202 @@
+use crate::{
+    cache::{
+        merge_changes, CacheEntry, FileBlockCache, FileCache, FilePageCache, FileTaskCache,
+        LogseqPage,
+    },
+    file_graph,
+    logseq::{self, LogseqSource, Properties, Tags},
+    status::{self, FileSyncStatus},
+};
+use chrono::Utc;
+use std::{collections::HashSet, path::Path};
+
+/// Syncs between the checks for deleted blocks
+const DELETION_CHECK_INTERVAL: u32 = 10;
+/// DB graphs are synced every minute, their cache is stale after missing a few
+/// syncs more than the status may lag behind
+const STALE_AFTER: i64 = status::WRITE_INTERVAL + 5 * 60 * 1000;
+
+/// Keeps the caches of a DB graph up to date. After the first sync only the
+/// blocks updated since the previous one are queried and merged into the caches.
+pub struct GraphSync {
+    pub source: Box<dyn LogseqSource + Send>,
+    status: FileSyncStatus,
+    /// When the last successful sync started, in milliseconds
+    last_sync: Option<i64>,
+    /// Tags of the last sync, pages are synced again when they change
+    tags: Option<Tags>,
+    /// Properties pulled with the pages, looked up again when checking for deleted blocks
+    properties: Option<Properties>,
+    syncs: u32,
+}
+
+impl GraphSync {
+    pub fn new(source: Box<dyn LogseqSource + Send>) -> Self {
+        GraphSync {
+            status: FileSyncStatus::for_graph(source.graph()),
+            source,
+            last_sync: None,
+            tags: None,
+            properties: None,
+            syncs: 0,
+        }
+    }
+
+    /// Syncs the caches and records the outcome in the status of the graph
+    pub fn sync(&mut self, property_names: &[String], sync_blocks: bool) {
+        match self.source.is_open() {
+            Ok(true) => {}
+            Ok(false) => return skip(&mut self.status, self.source.graph()),
+            Err(e) => {
+                let graph = self.source.graph();
+                return record(&mut self.status, graph, &Err(e), Some(STALE_AFTER));
+            }
+        }
+
+        let started_at = Utc::now().timestamp_millis();
+        let since = self.last_sync;
+        let result = self.sync_changes(property_names, sync_blocks);
+        // a failed sync may have updated some of the caches, start over
+        self.last_sync = result.is_ok().then_some(started_at);
+        self.syncs += 1;
+
+        let graph = self.source.graph();
+        log::debug!(
+            "queried graph={:?} incremental={} millis={}",
+            graph,
+            since.is_some(),
+            Utc::now().timestamp_millis() - started_at
+        );
+        record(&mut self.status, graph, &result, Some(STALE_AFTER));
+    }
+
+    /// Number of pages in the cache after the sync
+    fn sync_changes(
+        &mut self,
+        property_names: &[String],
+        sync_blocks: bool,
+    ) -> Result<usize, String> {
+        let source = self.source.as_ref();
+        let graph = source.graph();
+        let tags = logseq::get_logseq_tags(source)?;
+
+        // renamed tags or changed icons are stored in every page using them
+        let since = self.last_sync.filter(|_| self.tags.as_ref() == Some(&tags));
+        let check_deleted = since.is_some() && self.syncs.is_multiple_of(DELETION_CHECK_INTERVAL);
+        let existing = |blocks: &str| -> Result<Option<HashSet<String>>, String> {
+            if check_deleted {
+                logseq::get_logseq_uuids(source, blocks).map(Some)
+            } else {
+                Ok(None)
+            }
+        };
+
+        let properties = match self
+            .properties
+            .take()
+            .filter(|_| since.is_some() && !check_deleted)
+        {
+            Some(properties) => properties,
+            None => logseq::get_logseq_properties(source, property_names)?,
+        };
+        let pages = logseq::get_logseq_pages(source, &tags, &properties, since)?;
+        let pages_changed = !pages.is_empty();
+        let page_count = update(
+            &FilePageCache::for_graph(graph),
+            pages,
+            since,
+            existing(logseq::PAGES)?,
+        )?;
+
+        // editing a task updates its page, so unchanged pages mean unchanged tasks
+        if since.is_none() || pages_changed || check_deleted {
+            let tasks = logseq::get_logseq_tasks(source, &tags, since)?;
+            update(
+                &FileTaskCache::for_graph(graph),
+                tasks,
+                since,
+                existing(logseq::TASKS)?,
+            )?;
+        }
+
+        if sync_blocks {
+            let blocks = logseq::get_logseq_blocks(source, &tags, since)?;
+            update(
+                &FileBlockCache::for_graph(graph),
+                blocks,
+                since,
+                existing(logseq::BLOCKS)?,
+            )?;
+        }
+        self.tags = Some(tags);
+        self.properties = Some(properties);
+        Ok(page_count)
+    }
+}
+
+/// Reads the pages and tasks of a file based graph into the caches, returns
+/// the number of pages
+pub fn sync_file_graph(graph: &str, dir: &Path) -> Result<usize, String> {
+    let pages = file_graph::read_file_graph(graph, dir);
+    let tasks = file_graph::file_graph_tasks(graph, &pages);
+    let pages: Vec<LogseqPage> = pages.into_iter().map(|(page, _)| page).collect();
+    FilePageCache::for_graph(graph)
+        .update_cache(&pages)
+        .map_err(|e| e.to_string())?;
+    FileTaskCache::for_graph(graph)
+        .update_cache(&tasks)
+        .map_err(|e| e.to_string())?;
+    Ok(pages.len())
+}
+
+/// Logs the outcome of a sync and stores it in the status of the graph
+pub fn record(
+    status: &mut FileSyncStatus,
+    graph: &str,
+    result: &Result<usize, String>,
+    stale_after: Option<i64>,
+) {
+    match result {
+        Ok(pages) => log::info!("synced graph={:?} pages={}", graph, pages),
+        Err(e) => log::error!("sync failed graph={:?} error={:?}", graph, e),
+    }
+    if let Err(e) = status.record(result, stale_after, Utc::now().timestamp_millis()) {
+        log::error!(
+            "status update failed graph={:?} error={:?}",
+            graph,
+            e.to_string()
+        );
+    }
+}
+
+/// Records that the graph wasn't synced because it isn't open in logseq
+fn skip(status: &mut FileSyncStatus, graph: &str) {
+    log::debug!("skipped graph={:?} reason=\"not open in logseq\"", graph);
+    if let Err(e) = status.record_skipped(Utc::now().timestamp_millis()) {
+        log::error!(
+            "status update failed graph={:?} error={:?}",
+            graph,
+            e.to_string()
+        );
+    }
+}
+
+/// Writes the entries of a full sync, or merges the ones updated `since`.
+/// Returns the number of cached entries.
+fn update<T: CacheEntry>(
+    cache: &FileCache<T>,
+    updated: Vec<T>,
+    since: Option<i64>,
+    existing: Option<HashSet<String>>,
+) -> Result<usize, String> {
+    let entries = match since {
+        Some(_) => merge_changes(
+            cache.load_cache().map_err(|e| e.to_string())?,
+            updated,
+            existing.as_ref(),
+        ),
+        None => updated,
+    };
+    cache.update_cache(&entries).map_err(|e| e.to_string())?;
+    Ok(entries.len())
+}
@@ -0,0 +1,159 @@
+use crate::{cache::LogseqTaskEntry, query::Query};
+use chrono::{Local, NaiveDate, TimeZone};
+use std::{cmp::Reverse, collections::BTreeMap};
+
+/// Statuses of finished tasks, hidden unless a `status:` filter is given
+const CLOSED_STATUSES: [&str; 2] = ["done", "canceled"];
+
+/// Day the task is due, its deadline or else its scheduled day
+pub fn due_date(task: &LogseqTaskEntry) -> Option<NaiveDate> {
+    let millis = task.deadline.or(task.scheduled)?;
+    Local
+        .timestamp_millis_opt(millis)
+        .single()
+        .map(|t| t.date_naive())
+}
+
+/// Higher for more urgent priorities, DB graphs use `urgent` to `low`
+fn priority_rank(priority: Option<&str>) -> u8 {
+    match priority {
+        Some("urgent") => 4,
+        Some("high") => 3,
+        Some("medium") => 2,
+        Some("low") => 1,
+        _ => 0,
+    }
+}
+
+/// Properties `status:` and `priority:` filters are matched against
+fn task_properties(task: &LogseqTaskEntry) -> BTreeMap<String, String> {
+    [("status", &task.status), ("priority", &task.priority)]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.clone()?)))
+        .collect()
+}
+
+/// Tasks matching the query, the ones due first. Tasks without a due day come
+/// last, by priority.
+pub fn filter_tasks(
+    tasks: Vec<LogseqTaskEntry>,
+    query: &Query,
+    today: NaiveDate,
+) -> Vec<LogseqTaskEntry> {
+    let show_closed = query.properties.iter().any(|(name, _)| name == "status");
+    let mut tasks: Vec<LogseqTaskEntry> = tasks
+        .into_iter()
+        .filter(|task| {
+            show_closed
+                || task
+                    .status
+                    .as_deref()
+                    .is_none_or(|s| !CLOSED_STATUSES.contains(&s))
+        })
+        .filter(|task| query.matches_words(&task.content))
+        .filter(|task| query.matches_properties(&task_properties(task)))
+        .filter(|task| {
+            query
+                .due
+                .is_none_or(|due| due_date(task).is_some_and(|date| due.includes(date, today)))
+        })
+        .collect();
+
+    tasks.sort_by(|a, b| {
+        let key = |task: &LogseqTaskEntry| {
+            let due = due_date(task);
+            (
+                due.is_none(),
+                due,
+                Reverse(priority_rank(task.priority.as_deref())),
+                Reverse(task.updated_at),
+            )
+        };
+        key(a).cmp(&key(b)).then_with(|| a.content.cmp(&b.content))
+    });
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+    fn task(
+        content: &str,
+        status: &str,
+        priority: Option<&str>,
+        due_in: Option<i64>,
+    ) -> LogseqTaskEntry {
+        let now = Local::now().timestamp_millis();
+        LogseqTaskEntry {
+            graph: "illef".to_string(),
+            content: content.to_string(),
+            uuid: String::new(),
+            page: "Work".to_string(),
+            status: Some(status.to_string()),
+            priority: priority.map(str::to_string),
+            scheduled: None,
+            deadline: due_in.map(|days| now + days * DAY_MILLIS),
+            tags: vec![],
+            updated_at: None,
+        }
+    }
+
+    fn contents(tasks: &[LogseqTaskEntry]) -> Vec<&str> {
+        tasks.iter().map(|t| t.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filter_tasks() {
+        let today = Local::now().date_naive();
+        let tasks = vec![
+            task("write report", "todo", Some("low"), None),
+            task("review report", "doing", Some("urgent"), None),
+            task("send invoice", "todo", None, Some(3)),
+            task("pay rent", "todo", None, Some(-1)),
+            task("plan trip", "todo", Some("high"), Some(20)),
+            task("file taxes", "done", None, Some(1)),
+        ];
+
+        assert_eq!(
+            contents(&filter_tasks(
+                tasks.clone(),
+                &Query::parse("task:", &[]),
+                today
+            )),
+            vec![
+                "pay rent",
+                "send invoice",
+                "plan trip",
+                "review report",
+                "write report"
+            ]
+        );
+        assert_eq!(
+            contents(&filter_tasks(
+                tasks.clone(),
+                &Query::parse("task: due:week", &[]),
+                today
+            )),
+            vec!["pay rent", "send invoice"]
+        );
+        assert_eq!(
+            contents(&filter_tasks(
+                tasks.clone(),
+                &Query::parse("task: report status:todo", &[]),
+                today
+            )),
+            vec!["write report"]
+        );
+        assert_eq!(
+            contents(&filter_tasks(
+                tasks,
+                &Query::parse("task: status:done", &[]),
+                today
+            )),
+            vec!["file taxes"]
+        );
+    }
+}
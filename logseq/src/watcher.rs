@@ -0,0 +1,103 @@
+use crate::file_graph::{is_page_file, PAGE_DIRS};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    path::Path,
+    sync::mpsc::{self, RecvTimeoutError},
+    time::Duration,
+};
+
+/// Page file in `pages/` or `journals/`, backups in `logseq/bak/` and
+/// `version-files/` are ignored
+fn is_graph_page(dir: &Path, path: &Path) -> bool {
+    is_page_file(path)
+        && path
+            .strip_prefix(dir)
+            .ok()
+            .and_then(|p| p.components().next())
+            .is_some_and(|c| PAGE_DIRS.iter().any(|d| c.as_os_str() == *d))
+}
+
+fn is_relevant(dir: &Path, event: &notify::Result<Event>) -> bool {
+    match event {
+        Ok(event) => {
+            !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|p| is_graph_page(dir, p))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Calls `on_change` whenever a page file of the graph changes.
+///
+/// Logseq writes a page on every edit, `on_change` runs once the graph was
+/// quiet for `debounce`. Blocks until the watcher stops.
+pub fn watch<F: FnMut()>(dir: &Path, debounce: Duration, mut on_change: F) -> notify::Result<()> {
+    // some backends report canonical paths, which are compared with `dir`
+    let dir = dir.canonicalize().unwrap_or(dir.to_path_buf());
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&dir, RecursiveMode::Recursive)?;
+
+    while let Ok(event) = rx.recv() {
+        if !is_relevant(&dir, &event) {
+            continue;
+        }
+
+        loop {
+            match rx.recv_timeout(debounce) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+        on_change();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_is_graph_page() {
+        let dir = Path::new("/home/user/notes");
+        assert!(is_graph_page(dir, &dir.join("pages/rust.md")));
+        assert!(is_graph_page(dir, &dir.join("journals/2024_01_03.md")));
+        assert!(!is_graph_page(dir, &dir.join("logseq/bak/pages/rust.md")));
+        assert!(!is_graph_page(dir, &dir.join("version-files/rust.md")));
+        assert!(!is_graph_page(dir, &dir.join("pages/logo.png")));
+    }
+
+    #[test]
+    fn test_watch_page_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let pages = dir.path().join("pages");
+        std::fs::create_dir(&pages).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let graph_dir = dir.path().to_path_buf();
+        thread::spawn(move || {
+            watch(&graph_dir, Duration::from_millis(300), || {
+                tx.send(()).unwrap()
+            })
+            .unwrap();
+        });
+        thread::sleep(Duration::from_millis(200));
+
+        std::fs::write(dir.path().join("logseq-config.edn"), "{}").unwrap();
+        let backups = dir.path().join("logseq/bak/pages");
+        std::fs::create_dir_all(&backups).unwrap();
+        std::fs::write(backups.join("rust.md"), "- backup").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(800)).is_err());
+
+        for i in 0..5 {
+            std::fs::write(pages.join("rust.md"), format!("- edit {}", i)).unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+}
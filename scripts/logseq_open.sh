@@ -0,0 +1,9 @@
+#!/bin/bash
+
+KEY=$1
+URL=$2
+
+xdg-open "$URL"
+
+# 최근 연 logseq page/block 정보를 남긴다 (ranking에 사용)
+echo "$KEY | $(date +%s)" >> ~/.cache/illef-findex-plugin/logseq.access_log
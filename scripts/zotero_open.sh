@@ -0,0 +1,9 @@
+#!/bin/bash
+
+KEY=$1
+TARGET=$2
+
+xdg-open "$TARGET"
+
+# 최근 연 zotero item 정보를 남긴다 (frecency 계산에 사용)
+echo "$KEY | $(date +%s)" >> ~/.cache/illef-findex-plugin/zotero.access_log
@@ -1,11 +1,18 @@
-use convert_case::{Case, Casing};
+use crate::{
+    dates::parse_datetime,
+    icons::{attachment_icon, item_type_icon},
+};
+use chrono::{DateTime, Utc};
 use html_parser::{Dom, Node};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
+    fmt,
     fs::File,
     io::{self, BufReader},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::LazyLock,
 };
 
 use thiserror::Error;
@@ -18,28 +25,285 @@ pub enum LoaderError {
     IoError(#[from] io::Error),
 }
 
+/// Problem with a single exported item, the rest of the export still loads.
+#[derive(Debug, PartialEq)]
+pub struct LoadWarning {
+    pub key: Option<String>,
+    pub message: String,
+    /// Item was left out instead of loaded with defaults
+    pub skipped: bool,
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "{}: {}", key, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[derive(Default)]
 pub struct ZoteroItem {
+    pub key: String,
+    pub item_type: String,
     pub title: String,
+    pub full_title: Option<String>,
     pub tags: Vec<String>,
+    pub creators: Vec<ZoteroCreator>,
+    pub year: Option<i32>,
+    pub publication: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+    pub pages: Option<String>,
+    pub publisher: Option<String>,
+    pub place: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    pub citation_key: Option<String>,
+    pub abstract_note: Option<String>,
+    pub extra: Option<String>,
+    /// Plain text of the item's notes
+    pub notes: Vec<String>,
+    pub attachments: Vec<ZoteroAttachment>,
     pub select: String,
     pub icon: String,
-    pub date_modified: String,
+    pub date_added: Option<DateTime<Utc>>,
+    /// Latest modification of the item or one of its notes
+    pub date_modified: Option<DateTime<Utc>>,
+}
+
+impl ZoteroItem {
+    /// First PDF or EPUB attachment, PDFs are preferred
+    pub fn file_attachment(&self) -> Option<&ZoteroAttachment> {
+        self.attachments
+            .iter()
+            .filter(|a| a.kind != AttachmentKind::Other)
+            .min_by_key(|a| a.kind != AttachmentKind::Pdf)
+    }
+}
+
+/// PDF highlight, note or underline, stored as a child of the attachment
+#[derive(Debug, Default)]
+pub struct ZoteroAnnotation {
+    pub key: String,
+    pub attachment_key: Option<String>,
+    pub text: Option<String>,
+    pub comment: Option<String>,
+    pub color: Option<String>,
+    /// 1-based page index
+    pub page: Option<u32>,
+    pub page_label: Option<String>,
+    pub date_modified: Option<DateTime<Utc>>,
+}
+
+impl ZoteroAnnotation {
+    /// Link opening the PDF at the annotation's page
+    pub fn open_link(&self) -> Option<String> {
+        let mut link = format!(
+            "zotero://open-pdf/library/items/{}",
+            self.attachment_key.as_ref()?
+        );
+        if let Some(page) = self.page {
+            link.push_str(&format!("?page={}&annotation={}", page, self.key));
+        } else {
+            link.push_str(&format!("?annotation={}", self.key));
+        }
+        Some(link)
+    }
+
+    /// Coloured emoji closest to zotero's annotation colours
+    pub fn icon(&self) -> String {
+        let emoji = match self.color.as_deref().map(str::to_lowercase).as_deref() {
+            Some("#ff6666") => "🔴",
+            Some("#f19837") => "🟠",
+            Some("#5fb236") => "🟢",
+            Some("#2ea8e5") => "🔵",
+            Some("#a28ae5") | Some("#e56eee") => "🟣",
+            Some("#aaaaaa") => "⚪",
+            _ => "🟡",
+        };
+        format!("emoji:{}", emoji)
+    }
+}
+
+impl From<Annotation> for ZoteroAnnotation {
+    fn from(annotation: Annotation) -> Self {
+        // `annotationPosition` is a JSON string in zotero's API and an object in some exports
+        let position = match annotation.position {
+            Some(serde_json::Value::String(position)) => serde_json::from_str(&position).ok(),
+            position => position,
+        };
+        let page_index = position
+            .as_ref()
+            .and_then(|p| p.get("pageIndex"))
+            .and_then(|i| i.as_u64());
+        let page = page_index
+            .map(|i| i as u32 + 1)
+            .or(annotation.page_label.as_ref().and_then(|l| l.parse().ok()));
+
+        ZoteroAnnotation {
+            key: annotation.key,
+            attachment_key: annotation.parent_item,
+            text: annotation.text.filter(|t| !t.is_empty()),
+            comment: annotation.comment.filter(|c| !c.is_empty()),
+            color: annotation.color,
+            page,
+            page_label: annotation.page_label,
+            date_modified: annotation
+                .date_modified
+                .or(annotation.date_added)
+                .as_deref()
+                .and_then(parse_datetime),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AttachmentKind {
+    Pdf,
+    Epub,
+    Other,
+}
+
+pub struct ZoteroAttachment {
+    pub key: Option<String>,
+    pub title: String,
+    pub path: Option<PathBuf>,
+    pub kind: AttachmentKind,
+    pub icon: String,
+}
+
+impl ZoteroAttachment {
+    /// Attachment file on disk.
+    ///
+    /// Exports don't always carry the path, stored files are looked up in
+    /// `<storage_dir>/<attachment key>/` then.
+    pub fn resolve_path(&self, storage_dir: &Path) -> Option<PathBuf> {
+        if let Some(path) = self.path.as_ref().filter(|p| p.exists()) {
+            return Some(path.clone());
+        }
+
+        let extension = match self.kind {
+            AttachmentKind::Pdf => "pdf",
+            AttachmentKind::Epub => "epub",
+            AttachmentKind::Other => return None,
+        };
+        std::fs::read_dir(storage_dir.join(self.key.as_ref()?))
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .find(|path| {
+                path.extension()
+                    .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+            })
+    }
+}
+
+// `uri` ends with the attachment key: http://zotero.org/users/1/items/ABCD1234
+fn attachment_key(attachment: &Attachment) -> Option<String> {
+    attachment
+        .uri
+        .as_deref()
+        .and_then(|u| u.rsplit('/').next())
+        .map(str::to_string)
+}
+
+impl From<Attachment> for ZoteroAttachment {
+    fn from(attachment: Attachment) -> Self {
+        let key = attachment_key(&attachment);
+        let path = attachment.path.map(PathBuf::from);
+        let extension = path
+            .as_ref()
+            .and_then(|p| p.extension())
+            .map(|e| e.to_string_lossy().to_lowercase());
+        let kind = match (attachment.content_type.as_deref(), extension.as_deref()) {
+            (Some("application/pdf"), _) | (_, Some("pdf")) => AttachmentKind::Pdf,
+            (Some("application/epub+zip"), _) | (_, Some("epub")) => AttachmentKind::Epub,
+            _ => AttachmentKind::Other,
+        };
+        let content_type = match kind {
+            AttachmentKind::Pdf => Some("application/pdf"),
+            AttachmentKind::Epub => Some("application/epub+zip"),
+            AttachmentKind::Other => attachment.content_type.as_deref(),
+        };
+
+        ZoteroAttachment {
+            key,
+            title: attachment.title.unwrap_or("<Unknown Attachment>".into()),
+            path,
+            kind,
+            icon: attachment_icon(content_type, attachment.link_mode.as_deref()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ZoteroCreator {
+    pub creator_type: String,
+    pub first_name: Option<String>,
+    pub last_name: String,
+}
+
+impl From<Creator> for ZoteroCreator {
+    fn from(creator: Creator) -> Self {
+        let creator_type = creator.creator_type.unwrap_or("author".into());
+        match (creator.last_name, creator.name) {
+            (Some(last_name), _) => ZoteroCreator {
+                creator_type,
+                first_name: creator.first_name,
+                last_name,
+            },
+            // single-field creators (institutions etc.) only have `name`
+            (None, name) => ZoteroCreator {
+                creator_type,
+                first_name: None,
+                last_name: name.unwrap_or_default(),
+            },
+        }
+    }
 }
 
 fn get_text_from_node(node: &Node) -> Option<String> {
     match node {
-        Node::Text(text) => return Some(text.clone()),
+        Node::Text(text) => Some(text.clone()),
         Node::Element(element) => {
             for node in element.children.iter() {
-                let text = get_text_from_node(&node);
+                let text = get_text_from_node(node);
                 if text.is_some() {
                     return text;
                 }
             }
-            return None;
+            None
+        }
+        _ => None,
+    }
+}
+
+fn collect_text(node: &Node, texts: &mut Vec<String>) {
+    match node {
+        Node::Text(text) => {
+            let text = html_escape::decode_html_entities(text);
+            if !text.trim().is_empty() {
+                texts.push(text.trim().to_string());
+            }
         }
-        _ => return None,
+        Node::Element(element) => element
+            .children
+            .iter()
+            .for_each(|node| collect_text(node, texts)),
+        _ => {}
+    }
+}
+
+/// Plain text of a note, notes are stored as HTML
+fn html_to_text(input: &str) -> String {
+    let mut texts = vec![];
+    if let Ok(dom) = Dom::parse(input) {
+        dom.children
+            .iter()
+            .for_each(|node| collect_text(node, &mut texts));
     }
+    texts.join(" ")
 }
 
 fn extract_first_tag_content(input: &str) -> Option<String> {
@@ -50,30 +314,83 @@ fn extract_first_tag_content(input: &str) -> Option<String> {
             }
         }
     }
-    return None;
+    None
 }
 
-fn item_type_to_icon(item_type: &str) -> String {
-    item_type.to_case(Case::Kebab)
+fn parse_year(date: &str) -> Option<i32> {
+    static YEAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(\d{4})\b").unwrap());
+    YEAR.captures(date)
+        .and_then(|c| c.get(1))
+        .and_then(|m| m.as_str().parse().ok())
 }
 
 impl Item {
-    fn into(self) -> Vec<ZoteroItem> {
+    /// Annotations nested in the item's attachments
+    fn annotations(&self) -> Vec<ZoteroAnnotation> {
+        self.attachments
+            .iter()
+            .flat_map(|attachment| {
+                let attachment_key = attachment_key(attachment);
+                attachment.annotations.iter().map(move |a| {
+                    let mut annotation = ZoteroAnnotation::from(a.clone());
+                    annotation.attachment_key = attachment_key.clone();
+                    annotation
+                })
+            })
+            .collect()
+    }
+
+    fn into(self, warnings: &mut Vec<LoadWarning>) -> Vec<ZoteroItem> {
+        let mut warn = |message: &str| {
+            warnings.push(LoadWarning {
+                key: Some(self.key.clone()),
+                message: message.to_string(),
+                skipped: false,
+            })
+        };
+        let date_added = self.date_added.as_deref().and_then(parse_datetime);
+        let own_date_modified = self
+            .date_modified
+            .as_deref()
+            .and_then(parse_datetime)
+            .or(date_added);
+        if own_date_modified.is_none() {
+            warn("missing or invalid dateModified");
+        }
+        let select = self.select.unwrap_or_else(|| {
+            warn("missing select link");
+            format!("zotero://select/library/items/{}", self.key)
+        });
+
         // TODO: self.note 도 꺼내서 별도 Item 으로 추가해야 한다
         let tags = self.tags.into_iter().map(|t| t.tag).collect();
 
+        let mut notes: Vec<String> = self
+            .notes
+            .iter()
+            .flatten()
+            .map(|n| html_to_text(&n.note))
+            .collect();
+
         let title: String = if self.item_type == "note" {
+            if let Some(note) = &self.note {
+                notes.push(html_to_text(note));
+            }
             self.note
-                .map(|n| extract_first_tag_content(&n))
-                .flatten()
+                .and_then(|n| extract_first_tag_content(&n))
                 .unwrap_or("<Unknown Title>".into())
         } else {
             self.short_title
-                .or(self.title)
+                .or(self.title.clone())
                 .unwrap_or("<Unknown Title>".into())
         };
 
-        let title = if let Some(publication_title) = self.publication_title {
+        let publication = self
+            .publication_title
+            .or(self.proceedings_title)
+            .or(self.book_title);
+
+        let title = if let Some(publication_title) = &publication {
             format!("{} | {} | {}", title, publication_title, self.item_type)
         } else {
             format!("{} | {}", title, self.item_type)
@@ -81,24 +398,56 @@ impl Item {
 
         let date_modified = self
             .notes
-            .map(|n| {
-                n.into_iter()
-                    .max_by(|a, b| a.date_modified.cmp(&b.date_modified))
-            })
+            .iter()
             .flatten()
-            .map(|n| std::cmp::max(n.date_modified, self.date_modified.clone()))
-            .unwrap_or(self.date_modified.clone());
+            .filter_map(|n| parse_datetime(&n.date_modified))
+            .chain(own_date_modified)
+            .max();
 
         vec![ZoteroItem {
+            key: self.key,
+            icon: if self.item_type == "attachment" {
+                attachment_icon(self.content_type.as_deref(), self.link_mode.as_deref())
+            } else {
+                item_type_icon(&self.item_type)
+            },
+            item_type: self.item_type,
             title,
+            full_title: self.title,
             tags,
-            select: self.select,
-            icon: item_type_to_icon(&self.item_type),
+            creators: self.creators.into_iter().map(ZoteroCreator::from).collect(),
+            year: self.date.as_deref().and_then(parse_year),
+            publication,
+            volume: self.volume,
+            issue: self.issue,
+            pages: self.pages,
+            publisher: self.publisher,
+            place: self.place,
+            doi: self.doi,
+            url: self.url,
+            citation_key: self.citation_key,
+            abstract_note: self.abstract_note,
+            extra: self.extra,
+            notes,
+            attachments: self
+                .attachments
+                .into_iter()
+                .map(ZoteroAttachment::from)
+                .collect(),
+            select,
+            date_added,
             date_modified,
         }]
     }
 }
 
+#[derive(Default)]
+pub struct LoadedItems {
+    pub items: Vec<ZoteroItem>,
+    pub annotations: Vec<ZoteroAnnotation>,
+    pub warnings: Vec<LoadWarning>,
+}
+
 pub struct BibTexLoader {}
 
 impl BibTexLoader {
@@ -109,8 +458,36 @@ impl BibTexLoader {
         Ok(data)
     }
 
-    pub fn load_items(data: ZoteroData) -> Vec<ZoteroItem> {
-        data.items.into_iter().map(|i| i.into()).flatten().collect()
+    /// Items of the export, items that don't match the expected schema are
+    /// skipped or loaded with defaults and reported as warnings.
+    pub fn load_items(data: ZoteroData) -> LoadedItems {
+        let mut loaded = LoadedItems::default();
+
+        for value in data.items {
+            let key = value
+                .get("key")
+                .and_then(|k| k.as_str())
+                .map(str::to_string);
+            let result = if value.get("itemType").and_then(|t| t.as_str()) == Some("annotation") {
+                serde_json::from_value::<Annotation>(value)
+                    .map(|a| loaded.annotations.push(ZoteroAnnotation::from(a)))
+            } else {
+                serde_json::from_value::<Item>(value).map(|item| {
+                    loaded.annotations.extend(item.annotations());
+                    loaded.items.extend(item.into(&mut loaded.warnings))
+                })
+            };
+
+            if let Err(e) = result {
+                loaded.warnings.push(LoadWarning {
+                    key,
+                    message: e.to_string(),
+                    skipped: true,
+                });
+            }
+        }
+
+        loaded
     }
 }
 
@@ -125,30 +502,135 @@ mod tests {
         let data = BibTexLoader::load_zotero(&*tilde("~/.cache/zotero/My-Library.json"));
         assert!(data.is_ok());
     }
+
+    #[test]
+    fn test_load_edge_cases() {
+        let data = BibTexLoader::load_zotero(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/edge-cases.json"
+        ))
+        .unwrap();
+        let LoadedItems {
+            items,
+            annotations,
+            warnings,
+        } = BibTexLoader::load_items(data);
+
+        assert_eq!(
+            items.iter().map(|i| i.key.as_str()).collect::<Vec<_>>(),
+            vec!["ATTACH01", "NOTE0001", "INST0001"]
+        );
+        assert_eq!(items[0].select, "zotero://select/library/items/ATTACH01");
+        assert_eq!(items[0].icon, "attachment-pdf");
+        assert_eq!(items[1].notes, vec!["Reading list"]);
+        assert_eq!(items[2].creators[0].last_name, "World Health Organization");
+
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(
+            annotations[0].date_modified,
+            parse_datetime("2024-03-01T10:00:00Z")
+        );
+        assert_eq!(
+            annotations[0].open_link().unwrap(),
+            "zotero://open-pdf/library/items/ATTACH01?page=3&annotation=ANNOT001"
+        );
+
+        let skipped: Vec<_> = warnings.iter().filter(|w| w.skipped).collect();
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].key, None);
+        assert_eq!(skipped[1].key.as_deref(), Some("BADTAGS1"));
+        assert!(warnings.contains(&LoadWarning {
+            key: Some("ATTACH01".into()),
+            message: "missing or invalid dateModified".into(),
+            skipped: false,
+        }));
+    }
+
+    #[test]
+    fn test_resolve_attachment_path() {
+        let storage_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(storage_dir.path().join("ABCD1234")).unwrap();
+        std::fs::write(storage_dir.path().join("ABCD1234/paper.PDF"), "").unwrap();
+
+        let attachment = ZoteroAttachment::from(Attachment {
+            title: Some("Full Text PDF".into()),
+            path: None,
+            uri: Some("http://zotero.org/users/1/items/ABCD1234".into()),
+            content_type: Some("application/pdf".into()),
+            link_mode: Some("imported_file".into()),
+            annotations: vec![],
+        });
+
+        assert_eq!(attachment.kind, AttachmentKind::Pdf);
+        assert_eq!(attachment.icon, "attachment-pdf");
+        assert_eq!(
+            attachment.resolve_path(storage_dir.path()),
+            Some(storage_dir.path().join("ABCD1234/paper.PDF"))
+        );
+        assert_eq!(attachment.resolve_path(Path::new("/nonexistent")), None);
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text("<div><h1>Summary</h1><p>Attention &amp; <b>transformers</b></p></div>"),
+            "Summary Attention & transformers"
+        );
+    }
+
+    #[test]
+    fn test_nested_annotations() {
+        let data: ZoteroData = serde_json::from_str(
+            r##"{"items": [{
+                "key": "ITEM0001",
+                "itemType": "journalArticle",
+                "attachments": [{
+                    "uri": "http://zotero.org/users/1/items/PDF00001",
+                    "annotations": [{
+                        "key": "ANNOT002",
+                        "annotationType": "highlight",
+                        "annotationText": "nested highlight",
+                        "annotationColor": "#5fb236",
+                        "annotationPosition": {"pageIndex": 4, "rects": []}
+                    }]
+                }]
+            }]}"##,
+        )
+        .unwrap();
+        let annotations = BibTexLoader::load_items(data).annotations;
+
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].icon(), "emoji:🟢");
+        assert_eq!(
+            annotations[0].open_link().unwrap(),
+            "zotero://open-pdf/library/items/PDF00001?page=5&annotation=ANNOT002"
+        );
+    }
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(parse_year("2019-03-01"), Some(2019));
+        assert_eq!(parse_year("March 2015"), Some(2015));
+        assert_eq!(parse_year("n.d."), None);
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ZoteroData {
+    #[serde(default)]
     config: Config,
-    collections: HashMap<String, Collection>,
-    items: Vec<Item>,
+    #[serde(default)]
+    collections: HashMap<String, serde_json::Value>,
+    // deserialized one by one in `load_items` so one bad item doesn't fail the export
+    items: Vec<serde_json::Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 struct Config {
     id: String,
     label: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Collection {
-    key: String,
-    parent: String,
-    name: String,
-    // collections: Vec<String>, TODO: Inner Collection
-    items: Vec<i64>,
-}
-
 #[derive(Serialize, Deserialize, Debug)]
 struct Item {
     key: String,
@@ -164,17 +646,89 @@ struct Item {
     library_catalog: Option<String>,
     #[serde(rename = "accessDate")]
     access_date: Option<String>,
+    #[serde(default)]
     tags: Vec<Tag>,
     collections: Option<Vec<String>>,
     #[serde(rename = "publicationTitle")]
     publication_title: Option<String>,
+    #[serde(rename = "proceedingsTitle")]
+    proceedings_title: Option<String>,
+    #[serde(rename = "bookTitle")]
+    book_title: Option<String>,
+    volume: Option<String>,
+    issue: Option<String>,
+    pages: Option<String>,
+    publisher: Option<String>,
+    place: Option<String>,
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+    url: Option<String>,
+    #[serde(rename = "abstractNote")]
+    abstract_note: Option<String>,
+    #[serde(rename = "contentType")]
+    content_type: Option<String>,
+    #[serde(rename = "linkMode")]
+    link_mode: Option<String>,
+    #[serde(default)]
+    creators: Vec<Creator>,
+    #[serde(rename = "citationKey")]
+    citation_key: Option<String>,
     #[serde(rename = "dateAdded")]
-    date_added: String,
+    date_added: Option<String>,
     #[serde(rename = "dateModified")]
-    date_modified: String,
+    date_modified: Option<String>,
     extra: Option<String>,
-    select: String,
+    select: Option<String>,
     notes: Option<Vec<Note>>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Attachment {
+    title: Option<String>,
+    path: Option<String>,
+    uri: Option<String>,
+    #[serde(rename = "contentType")]
+    content_type: Option<String>,
+    #[serde(rename = "linkMode")]
+    link_mode: Option<String>,
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Annotation {
+    key: String,
+    #[serde(rename = "parentItem")]
+    parent_item: Option<String>,
+    #[serde(rename = "annotationType")]
+    annotation_type: Option<String>,
+    #[serde(rename = "annotationText")]
+    text: Option<String>,
+    #[serde(rename = "annotationComment")]
+    comment: Option<String>,
+    #[serde(rename = "annotationColor")]
+    color: Option<String>,
+    #[serde(rename = "annotationPageLabel")]
+    page_label: Option<String>,
+    #[serde(rename = "annotationPosition")]
+    position: Option<serde_json::Value>,
+    #[serde(rename = "dateAdded")]
+    date_added: Option<String>,
+    #[serde(rename = "dateModified")]
+    date_modified: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Creator {
+    #[serde(rename = "creatorType")]
+    creator_type: Option<String>,
+    #[serde(rename = "firstName")]
+    first_name: Option<String>,
+    #[serde(rename = "lastName")]
+    last_name: Option<String>,
+    name: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -185,13 +739,15 @@ struct Tag {
 #[derive(Serialize, Deserialize, Debug)]
 struct Note {
     #[serde(rename = "itemType")]
-    item_type: String,
+    item_type: Option<String>,
     #[serde(rename = "parentItem")]
-    parent_item: String,
+    parent_item: Option<String>,
+    #[serde(default)]
     note: String,
+    #[serde(default)]
     tags: Vec<Tag>,
-    #[serde(rename = "dateAdded")]
+    #[serde(rename = "dateAdded", default)]
     date_added: String,
-    #[serde(rename = "dateModified")]
+    #[serde(rename = "dateModified", default)]
     date_modified: String,
 }
@@ -0,0 +1,406 @@
+use crate::bibtex_loader::{ZoteroCreator, ZoteroItem};
+
+/// Text that can be copied to the clipboard for an item instead of opening it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CitationFormat {
+    Key,
+    Pandoc,
+    Apa,
+    Chicago,
+    Bibtex,
+}
+
+impl CitationFormat {
+    pub fn parse(name: &str) -> Option<CitationFormat> {
+        match name {
+            "key" => Some(CitationFormat::Key),
+            "pandoc" => Some(CitationFormat::Pandoc),
+            "apa" => Some(CitationFormat::Apa),
+            "chicago" => Some(CitationFormat::Chicago),
+            "bibtex" | "bib" => Some(CitationFormat::Bibtex),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self, item: &ZoteroItem) -> String {
+        match self {
+            CitationFormat::Key => citation_key(item).to_string(),
+            CitationFormat::Pandoc => format!("[@{}]", citation_key(item)),
+            CitationFormat::Apa => apa(item),
+            CitationFormat::Chicago => chicago(item),
+            CitationFormat::Bibtex => bibtex(item),
+        }
+    }
+}
+
+// items exported without Better BibTeX have no citation key, the zotero key is unique as well
+fn citation_key(item: &ZoteroItem) -> &str {
+    item.citation_key.as_deref().unwrap_or(&item.key)
+}
+
+fn authors(item: &ZoteroItem) -> Vec<&ZoteroCreator> {
+    let authors: Vec<_> = item
+        .creators
+        .iter()
+        .filter(|c| c.creator_type == "author")
+        .collect();
+    if authors.is_empty() {
+        item.creators.iter().collect()
+    } else {
+        authors
+    }
+}
+
+fn initials(first_name: &str) -> String {
+    first_name
+        .split_whitespace()
+        .map(|name| {
+            name.split('-')
+                .filter_map(|part| part.chars().next())
+                .map(|c| format!("{}.", c))
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn join_names(names: Vec<String>, conjunction: &str) -> String {
+    match names.len() {
+        0 => String::new(),
+        1 => names[0].clone(),
+        2 if conjunction == "&" => format!("{}, & {}", names[0], names[1]),
+        2 => format!("{} {} {}", names[0], conjunction, names[1]),
+        n => format!(
+            "{}, {} {}",
+            names[..n - 1].join(", "),
+            conjunction,
+            names[n - 1]
+        ),
+    }
+}
+
+fn en_dash_pages(pages: &str) -> String {
+    pages.replace("--", "–").replace('-', "–")
+}
+
+fn end_with_period(text: &str) -> String {
+    if text.ends_with(['.', '?', '!']) {
+        text.to_string()
+    } else {
+        format!("{}.", text)
+    }
+}
+
+fn title(item: &ZoteroItem) -> &str {
+    item.full_title.as_deref().unwrap_or("<Unknown Title>")
+}
+
+fn link(item: &ZoteroItem) -> Option<String> {
+    item.doi
+        .as_ref()
+        .map(|doi| format!("https://doi.org/{}", doi))
+        .or(item.url.clone())
+}
+
+fn is_book(item: &ZoteroItem) -> bool {
+    matches!(item.item_type.as_str(), "book" | "thesis" | "report")
+}
+
+/// APA 7 reference list entry.
+fn apa(item: &ZoteroItem) -> String {
+    let authors = join_names(
+        authors(item)
+            .into_iter()
+            .map(|c| match &c.first_name {
+                Some(first_name) => format!("{}, {}", c.last_name, initials(first_name)),
+                None => c.last_name.clone(),
+            })
+            .collect(),
+        "&",
+    );
+    let year = item
+        .year
+        .map(|y| y.to_string())
+        .unwrap_or("n.d.".to_string());
+
+    let mut parts = vec![];
+    if !authors.is_empty() {
+        parts.push(end_with_period(&authors));
+    }
+    parts.push(format!("({}).", year));
+    parts.push(end_with_period(title(item)));
+
+    if is_book(item) {
+        if let Some(publisher) = &item.publisher {
+            parts.push(end_with_period(publisher));
+        }
+    } else if let Some(publication) = &item.publication {
+        let mut source = publication.clone();
+        if let Some(volume) = &item.volume {
+            source.push_str(&format!(", {}", volume));
+            if let Some(issue) = &item.issue {
+                source.push_str(&format!("({})", issue));
+            }
+        }
+        if let Some(pages) = &item.pages {
+            source.push_str(&format!(", {}", en_dash_pages(pages)));
+        }
+        parts.push(end_with_period(&source));
+    }
+
+    if let Some(link) = link(item) {
+        parts.push(link);
+    }
+
+    parts.join(" ")
+}
+
+/// Chicago author-date reference list entry.
+fn chicago(item: &ZoteroItem) -> String {
+    let authors = join_names(
+        authors(item)
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| match (&c.first_name, i) {
+                (Some(first_name), 0) => format!("{}, {}", c.last_name, first_name),
+                (Some(first_name), _) => format!("{} {}", first_name, c.last_name),
+                (None, _) => c.last_name.clone(),
+            })
+            .collect(),
+        "and",
+    );
+    let year = item
+        .year
+        .map(|y| y.to_string())
+        .unwrap_or("n.d.".to_string());
+
+    let mut parts = vec![];
+    if !authors.is_empty() {
+        parts.push(end_with_period(&authors));
+    }
+    parts.push(format!("{}.", year));
+
+    if is_book(item) {
+        parts.push(end_with_period(title(item)));
+        match (&item.place, &item.publisher) {
+            (Some(place), Some(publisher)) => parts.push(format!("{}: {}.", place, publisher)),
+            (None, Some(publisher)) => parts.push(end_with_period(publisher)),
+            _ => {}
+        }
+    } else {
+        parts.push(format!("“{}”", end_with_period(title(item))));
+        if let Some(publication) = &item.publication {
+            let mut source = publication.clone();
+            if let Some(volume) = &item.volume {
+                source.push_str(&format!(" {}", volume));
+            }
+            if let Some(issue) = &item.issue {
+                source.push_str(&format!(" ({})", issue));
+            }
+            if let Some(pages) = &item.pages {
+                source.push_str(&format!(": {}", en_dash_pages(pages)));
+            }
+            parts.push(end_with_period(&source));
+        }
+    }
+
+    if let Some(link) = link(item) {
+        parts.push(end_with_period(&link));
+    }
+
+    parts.join(" ")
+}
+
+fn bibtex_type(item_type: &str) -> &'static str {
+    match item_type {
+        "journalArticle" | "magazineArticle" | "newspaperArticle" => "article",
+        "book" => "book",
+        "bookSection" => "incollection",
+        "conferencePaper" => "inproceedings",
+        "thesis" => "phdthesis",
+        "report" => "techreport",
+        _ => "misc",
+    }
+}
+
+/// Escapes LaTeX special characters, braces are escaped as well so an
+/// unbalanced brace can't end the field early
+fn bibtex_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// BibTeX entry in the layout Better BibTeX exports.
+fn bibtex(item: &ZoteroItem) -> String {
+    let entry_type = bibtex_type(&item.item_type);
+    let mut fields = vec![("title", format!("{{{}}}", bibtex_escape(title(item))))];
+
+    let authors = authors(item)
+        .into_iter()
+        .map(|c| match &c.first_name {
+            Some(first_name) => format!(
+                "{}, {}",
+                bibtex_escape(&c.last_name),
+                bibtex_escape(first_name)
+            ),
+            None => format!("{{{}}}", bibtex_escape(&c.last_name)),
+        })
+        .collect::<Vec<_>>();
+    if !authors.is_empty() {
+        fields.push(("author", authors.join(" and ")));
+    }
+
+    if let Some(publication) = &item.publication {
+        let field = match entry_type {
+            "article" => "journal",
+            "misc" => "howpublished",
+            _ => "booktitle",
+        };
+        fields.push((field, bibtex_escape(publication)));
+    }
+    if let Some(volume) = &item.volume {
+        fields.push(("volume", volume.clone()));
+    }
+    if let Some(issue) = &item.issue {
+        fields.push(("number", issue.clone()));
+    }
+    if let Some(pages) = &item.pages {
+        fields.push((
+            "pages",
+            pages
+                .replace('–', "-")
+                .replace("--", "-")
+                .replace('-', "--"),
+        ));
+    }
+    if let Some(publisher) = &item.publisher {
+        let field = match entry_type {
+            "phdthesis" => "school",
+            "techreport" => "institution",
+            _ => "publisher",
+        };
+        fields.push((field, bibtex_escape(publisher)));
+    }
+    if let Some(place) = &item.place {
+        fields.push(("address", bibtex_escape(place)));
+    }
+    if let Some(year) = item.year {
+        fields.push(("year", year.to_string()));
+    }
+    if let Some(doi) = &item.doi {
+        fields.push(("doi", doi.clone()));
+    }
+    if let Some(url) = &item.url {
+        fields.push(("url", url.clone()));
+    }
+
+    format!(
+        "@{}{{{},\n{}\n}}",
+        entry_type,
+        citation_key(item),
+        fields
+            .into_iter()
+            .map(|(name, value)| format!("  {} = {{{}}}", name, value))
+            .collect::<Vec<_>>()
+            .join(",\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    fn creator(first_name: &str, last_name: &str) -> ZoteroCreator {
+        ZoteroCreator {
+            creator_type: "author".into(),
+            first_name: Some(first_name.into()),
+            last_name: last_name.into(),
+        }
+    }
+
+    fn article() -> ZoteroItem {
+        ZoteroItem {
+            key: "ABCD1234".into(),
+            item_type: "journalArticle".into(),
+            full_title: Some("Attention is all you need".into()),
+            creators: vec![
+                creator("Ashish", "Vaswani"),
+                creator("Noam", "Shazeer"),
+                creator("Niki", "Parmar"),
+            ],
+            year: Some(2017),
+            publication: Some("Advances in Neural Information Processing Systems".into()),
+            volume: Some("30".into()),
+            issue: Some("2".into()),
+            pages: Some("5998-6008".into()),
+            doi: Some("10.5555/3295222.3295349".into()),
+            citation_key: Some("vaswani2017".into()),
+            ..Default::default()
+        }
+    }
+
+    fn book() -> ZoteroItem {
+        ZoteroItem {
+            key: "EFGH5678".into(),
+            item_type: "book".into(),
+            full_title: Some("The TeXbook".into()),
+            creators: vec![creator("Donald E.", "Knuth")],
+            year: Some(1984),
+            publisher: Some("Addison-Wesley".into()),
+            place: Some("Reading, MA".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_key() {
+        assert_eq!(CitationFormat::Key.format(&article()), "vaswani2017");
+        assert_eq!(CitationFormat::Pandoc.format(&article()), "[@vaswani2017]");
+        assert_eq!(CitationFormat::Pandoc.format(&book()), "[@EFGH5678]");
+    }
+
+    #[test]
+    fn test_apa() {
+        assert_snapshot!("apa_article", CitationFormat::Apa.format(&article()));
+        assert_snapshot!("apa_book", CitationFormat::Apa.format(&book()));
+    }
+
+    #[test]
+    fn test_chicago() {
+        assert_snapshot!(
+            "chicago_article",
+            CitationFormat::Chicago.format(&article())
+        );
+        assert_snapshot!("chicago_book", CitationFormat::Chicago.format(&book()));
+    }
+
+    #[test]
+    fn test_bibtex() {
+        assert_snapshot!("bibtex_article", CitationFormat::Bibtex.format(&article()));
+        assert_snapshot!("bibtex_book", CitationFormat::Bibtex.format(&book()));
+        assert_snapshot!(
+            "bibtex_special_characters",
+            CitationFormat::Bibtex.format(&ZoteroItem {
+                full_title: Some("Set {A} of C:\\path, 50% off}".into()),
+                creators: vec![ZoteroCreator {
+                    creator_type: "author".into(),
+                    first_name: None,
+                    last_name: "R&D {Lab}".into(),
+                }],
+                ..book()
+            })
+        );
+    }
+}
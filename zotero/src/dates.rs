@@ -0,0 +1,72 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Parses zotero timestamps, `2024-01-03T10:00:00Z` in exports and
+/// `2024-01-03 10:00:00` (UTC) in the database.
+pub fn parse_datetime(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|d| d.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|d| d.and_utc())
+        })
+}
+
+fn plural(count: i64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+/// `3 days ago` style description of `date`
+pub fn relative_time(date: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now.signed_duration_since(date);
+
+    if elapsed.num_minutes() < 1 {
+        "just now".to_string()
+    } else if elapsed.num_hours() < 1 {
+        plural(elapsed.num_minutes(), "minute")
+    } else if elapsed.num_days() < 1 {
+        plural(elapsed.num_hours(), "hour")
+    } else if elapsed.num_days() < 30 {
+        plural(elapsed.num_days(), "day")
+    } else if elapsed.num_days() < 365 {
+        plural(elapsed.num_days() / 30, "month")
+    } else {
+        plural(elapsed.num_days() / 365, "year")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_parse_datetime() {
+        let expected = DateTime::parse_from_rfc3339("2024-01-03T10:00:00Z").unwrap();
+        assert_eq!(
+            parse_datetime("2024-01-03T10:00:00Z"),
+            Some(expected.into())
+        );
+        assert_eq!(parse_datetime("2024-01-03 10:00:00"), Some(expected.into()));
+        assert_eq!(parse_datetime("yesterday"), None);
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now = parse_datetime("2026-10-19T12:00:00Z").unwrap();
+        assert_eq!(relative_time(now - Duration::seconds(10), now), "just now");
+        assert_eq!(
+            relative_time(now - Duration::minutes(5), now),
+            "5 minutes ago"
+        );
+        assert_eq!(relative_time(now - Duration::hours(1), now), "1 hour ago");
+        assert_eq!(relative_time(now - Duration::days(3), now), "3 days ago");
+        assert_eq!(relative_time(now - Duration::days(65), now), "2 months ago");
+        assert_eq!(relative_time(now - Duration::days(800), now), "2 years ago");
+    }
+}
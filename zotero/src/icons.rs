@@ -0,0 +1,172 @@
+use convert_case::{Case, Casing};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// Used when there is no icon for an item type
+const FALLBACK_ICON: &str = "document";
+
+/// Icon name for an item type, `journalArticle` -> `journal-article`.
+pub fn item_type_icon(item_type: &str) -> String {
+    item_type.to_case(Case::Kebab)
+}
+
+/// Icon name for an attachment, following zotero's own attachment icons.
+pub fn attachment_icon(content_type: Option<&str>, link_mode: Option<&str>) -> String {
+    let linked_file = link_mode == Some("linked_file");
+    match (content_type, link_mode) {
+        (_, Some("linked_url")) => "attachment-web-link",
+        (Some("application/pdf"), _) if linked_file => "attachment-pdf-link",
+        (Some("application/pdf"), _) => "attachment-pdf",
+        (Some("application/epub+zip"), _) if linked_file => "attachment-epub-link",
+        (Some("application/epub+zip"), _) => "attachment-epub",
+        (Some("text/html"), _) if !linked_file => "attachment-snapshot",
+        _ => "attachment-link",
+    }
+    .to_string()
+}
+
+/// Resolves icon names to the SVGs available in the icon directory.
+pub struct IconResolver {
+    dir: PathBuf,
+    icons: HashSet<String>,
+}
+
+impl IconResolver {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        let icons = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().is_some_and(|e| e == "svg"))
+                    .filter_map(|p| Some(p.file_stem()?.to_string_lossy().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        IconResolver {
+            dir: dir.as_ref().to_path_buf(),
+            icons,
+        }
+    }
+
+    pub fn resolve(&self, icon: &str) -> PathBuf {
+        let icon = if self.icons.contains(icon) {
+            icon
+        } else {
+            FALLBACK_ICON
+        };
+        self.dir.join(format!("{}.svg", icon))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundled_icons() -> IconResolver {
+        IconResolver::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../assets/zotero-icons"
+        ))
+    }
+
+    #[test]
+    fn test_item_types_have_icons() {
+        // https://api.zotero.org/itemTypes
+        let item_types = [
+            "artwork",
+            "audioRecording",
+            "bill",
+            "blogPost",
+            "book",
+            "bookSection",
+            "case",
+            "computerProgram",
+            "conferencePaper",
+            "dataset",
+            "dictionaryEntry",
+            "document",
+            "email",
+            "encyclopediaArticle",
+            "film",
+            "forumPost",
+            "hearing",
+            "instantMessage",
+            "interview",
+            "journalArticle",
+            "letter",
+            "magazineArticle",
+            "manuscript",
+            "map",
+            "newspaperArticle",
+            "note",
+            "patent",
+            "podcast",
+            "preprint",
+            "presentation",
+            "radioBroadcast",
+            "report",
+            "standard",
+            "statute",
+            "thesis",
+            "tvBroadcast",
+            "videoRecording",
+            "webpage",
+        ];
+        let icons = bundled_icons();
+
+        for item_type in item_types {
+            let icon = item_type_icon(item_type);
+            assert_eq!(
+                icons.resolve(&icon),
+                icons.dir.join(format!("{}.svg", icon)),
+                "{} has no icon",
+                item_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_attachment_icons() {
+        let icons = bundled_icons();
+        let attachments = [
+            (
+                Some("application/pdf"),
+                Some("imported_file"),
+                "attachment-pdf",
+            ),
+            (
+                Some("application/pdf"),
+                Some("linked_file"),
+                "attachment-pdf-link",
+            ),
+            (Some("application/epub+zip"), None, "attachment-epub"),
+            (
+                Some("application/epub+zip"),
+                Some("linked_file"),
+                "attachment-epub-link",
+            ),
+            (
+                Some("text/html"),
+                Some("imported_url"),
+                "attachment-snapshot",
+            ),
+            (None, Some("linked_url"), "attachment-web-link"),
+            (Some("image/png"), Some("linked_file"), "attachment-link"),
+        ];
+
+        for (content_type, link_mode, expected) in attachments {
+            let icon = attachment_icon(content_type, link_mode);
+            assert_eq!(icon, expected);
+            assert!(icons.icons.contains(&icon));
+        }
+    }
+
+    #[test]
+    fn test_fallback_icon() {
+        let icons = bundled_icons();
+        assert_eq!(icons.resolve("annotation"), icons.dir.join("document.svg"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::bibtex_loader::{ZoteroAnnotation, ZoteroItem};
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Inverted index over the abstract, notes and extra field of items.
+///
+/// Items are identified by their position in the slice the index was built from.
+/// The same index is used for annotations.
+pub struct FullTextIndex {
+    tokens: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl FullTextIndex {
+    pub fn new(items: &[ZoteroItem]) -> Self {
+        Self::from_documents(items.iter().map(|item| {
+            item.abstract_note
+                .iter()
+                .chain(item.extra.iter())
+                .chain(item.notes.iter())
+        }))
+    }
+
+    /// Index over the highlighted text and comment of annotations
+    pub fn for_annotations(annotations: &[ZoteroAnnotation]) -> Self {
+        Self::from_documents(
+            annotations
+                .iter()
+                .map(|a| a.text.iter().chain(a.comment.iter())),
+        )
+    }
+
+    fn from_documents<'a, D: Iterator<Item = &'a String>>(
+        documents: impl Iterator<Item = D>,
+    ) -> Self {
+        let mut tokens: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+
+        for (id, texts) in documents.enumerate() {
+            for text in texts {
+                for token in tokenize(text) {
+                    tokens.entry(token).or_default().insert(id);
+                }
+            }
+        }
+
+        FullTextIndex { tokens }
+    }
+
+    /// Items with a token starting with the word
+    fn search_word(&self, word: &str) -> HashSet<usize> {
+        self.tokens
+            .range(word.to_string()..)
+            .take_while(|(token, _)| token.starts_with(word))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect()
+    }
+
+    /// Items whose body text contains all of the words
+    pub fn search(&self, words: &[&str]) -> HashSet<usize> {
+        let mut words = words.iter().flat_map(|w| tokenize(w)).peekable();
+        if words.peek().is_none() {
+            return HashSet::new();
+        }
+
+        words
+            .fold(None, |ids: Option<HashSet<usize>>, word| {
+                let found = self.search_word(&word);
+                Some(match ids {
+                    Some(ids) => ids.intersection(&found).copied().collect(),
+                    None => found,
+                })
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let items = vec![
+            ZoteroItem {
+                abstract_note: Some("We propose the Transformer, based on attention.".into()),
+                ..Default::default()
+            },
+            ZoteroItem {
+                notes: vec!["Attention은 중요하다".into()],
+                extra: Some("arXiv: 1706.03762".into()),
+                ..Default::default()
+            },
+        ];
+        let index = FullTextIndex::new(&items);
+
+        assert_eq!(index.search(&["attention"]), HashSet::from([0, 1]));
+        assert_eq!(index.search(&["Transform"]), HashSet::from([0]));
+        assert_eq!(index.search(&["attention", "arxiv"]), HashSet::from([1]));
+        assert_eq!(index.search(&["중요"]), HashSet::from([1]));
+        assert_eq!(index.search(&["missing"]), HashSet::new());
+        assert_eq!(index.search(&[]), HashSet::new());
+    }
+}
@@ -1,68 +1,333 @@
 mod bibtex_loader;
+mod citation;
+mod dates;
+mod icons;
+mod index;
+mod library;
+mod query;
+mod usage;
+mod watcher;
 
 use abi_stable::std_types::*;
-use bibtex_loader::{BibTexLoader, ZoteroItem};
+use bibtex_loader::ZoteroItem;
+use chrono::Utc;
+use dates::relative_time;
 use findex_plugin::{define_plugin, ApplicationCommand, FResult};
+use icons::IconResolver;
+use library::Library;
+use query::{Action, Query, SortMode};
 use shellexpand::tilde;
+use std::{
+    cmp::Reverse, collections::HashMap, path::PathBuf, sync::OnceLock, thread, time::Duration,
+};
+use usage::{FileUsageCache, ItemUsage};
+
+const DEFAULT_ICON_DIR: &str = "~/.cache/illef-findex-plugin/zotero-icons";
+const DEFAULT_LIBRARY_FILES: &str = "~/.cache/zotero/My-Library.json";
+const OPEN_SCRIPT: &str = "~/.cache/illef-findex-plugin/scripts/zotero_open.sh";
+
+static STORAGE_DIR: OnceLock<PathBuf> = OnceLock::new();
+static ICONS: OnceLock<IconResolver> = OnceLock::new();
+
+fn init(config: &RHashMap<RString, RString>) -> RResult<(), RString> {
+    let storage_dir = config
+        .get("storage-dir")
+        .map(|d| d.to_string())
+        .unwrap_or("~/Zotero/storage".to_string());
+    let _ = STORAGE_DIR.set(PathBuf::from(&*tilde(&storage_dir)));
+
+    let icon_dir = config
+        .get("icon-dir")
+        .map(|d| d.to_string())
+        .unwrap_or(DEFAULT_ICON_DIR.to_string());
+    let _ = ICONS.set(IconResolver::new(&*tilde(&icon_dir)));
+
+    // comma separated Better BibTeX JSON exports
+    let files: Vec<PathBuf> = config
+        .get("library-files")
+        .map(|f| f.to_string())
+        .unwrap_or(DEFAULT_LIBRARY_FILES.to_string())
+        .split(',')
+        .map(|f| PathBuf::from(&*tilde(f.trim())))
+        .collect();
+    thread::spawn(move || {
+        library::reload(&files);
+        if let Err(e) = watcher::watch(&files, Duration::from_secs(1), || library::reload(&files)) {
+            eprintln!("Failed to watch zotero library: {}", e);
+        }
+    });
+    thread::spawn(|| loop {
+        if let Err(e) = FileUsageCache::default().update_usage() {
+            eprintln!("Failed to update zotero usage: {}", e);
+        }
+        thread::sleep(Duration::from_secs(60));
+    });
 
-fn init(_: &RHashMap<RString, RString>) -> RResult<(), RString> {
     ROk(())
 }
 
-fn search(mut items: Vec<ZoteroItem>, search: &str) -> Vec<ZoteroItem> {
-    items.sort_by(|a, b| b.date_modified.cmp(&a.date_modified));
+fn storage_dir() -> &'static PathBuf {
+    STORAGE_DIR.get_or_init(|| PathBuf::from(&*tilde("~/Zotero/storage")))
+}
 
-    return if search.trim().len() == 0 {
-        items
+fn icons() -> &'static IconResolver {
+    ICONS.get_or_init(|| IconResolver::new(&*tilde(DEFAULT_ICON_DIR)))
+}
+
+/// Positions of `items` in the query's sort order, the default order puts
+/// frequently opened items first
+fn ranked_ids(library: &Library, query: &Query, usage: &HashMap<String, ItemUsage>) -> Vec<usize> {
+    let mut ids = library.ordered_ids(query.sort);
+    if query.sort == SortMode::Modified {
+        let now = Utc::now().timestamp().max(0) as u64;
+        ids.sort_by_key(|id| {
+            Reverse(
+                usage
+                    .get(&library.items[*id].key)
+                    .map_or(0, |u| u.frecency(now)),
+            )
+        });
+    }
+    ids
+}
+
+fn matching_ids(
+    library: &Library,
+    query: &Query,
+    usage: &HashMap<String, ItemUsage>,
+) -> Vec<usize> {
+    let words = query.full_text_words();
+    let full_text = (!words.is_empty()).then(|| library.index.search(&words));
+
+    ranked_ids(library, query, usage)
+        .into_iter()
+        .filter(|id| {
+            query.matches(&library.items[*id])
+                && full_text.as_ref().is_none_or(|ids| ids.contains(id))
+        })
+        .take(10)
+        .collect()
+}
+
+fn search<'a>(
+    library: &'a Library,
+    query: &Query,
+    usage: &HashMap<String, ItemUsage>,
+) -> Vec<&'a ZoteroItem> {
+    let ids = if query.is_empty() {
+        ranked_ids(library, query, usage)
     } else {
-        for keyword in search.split(" ") {
-            items = if keyword.starts_with("#") && keyword.len() > 1 {
-                items
-                    .into_iter()
-                    .filter(|i| {
-                        i.tags
-                            .iter()
-                            .filter(|t| t.to_lowercase().contains(&keyword[1..]))
-                            .count()
-                            > 0
-                    })
-                    .collect()
-            } else {
-                items
-                    .into_iter()
-                    .filter(|i| i.title.to_lowercase().contains(&keyword))
-                    .collect()
-            };
+        let ids = matching_ids(library, query, usage);
+        match query.full_text_fallback() {
+            Some(fallback) if ids.is_empty() => matching_ids(library, &fallback, usage),
+            _ => ids,
         }
-        items.into_iter().take(10).collect()
     };
+
+    ids.into_iter().map(|id| &library.items[id]).collect()
 }
 
-fn handle_query(query: RStr) -> RVec<FResult> {
-    if let Ok(data) = BibTexLoader::load_zotero(&*tilde("~/.cache/zotero/My-Library.json")) {
-        return search(BibTexLoader::load_items(data), &query)
-            .into_iter()
-            .map(|i| FResult {
-                cmd: ApplicationCommand::Command(RString::from(format!("xdg-open {}", i.select))),
-                icon: RString::from(&*tilde(&format!(
-                    "~/.cache/illef-findex-plugin/zotero-icons/{}.svg",
-                    i.icon
+fn search_annotations(library: &Library, query: &Query) -> Vec<FResult> {
+    let words = query.text_words();
+    if words.is_empty() {
+        return vec![];
+    }
+
+    let mut ids: Vec<usize> = library
+        .annotation_index
+        .search(&words)
+        .into_iter()
+        .collect();
+    ids.sort();
+
+    // filters apply to the annotated item, annotations without one only match unfiltered queries
+    ids.into_iter()
+        .map(|id| &library.annotations[id])
+        .filter(|a| {
+            library
+                .annotation_parent(a)
+                .map_or(!query.has_filters(), |item| query.matches_filters(item))
+        })
+        .filter_map(|a| Some((a, a.open_link()?)))
+        .take(5)
+        .map(|(a, link)| {
+            let page = a
+                .page_label
+                .clone()
+                .or(a.page.map(|p| p.to_string()))
+                .map(|p| format!("p. {}", p));
+            let parent = library.annotation_parent(a).map(|i| i.title.clone());
+
+            FResult {
+                cmd: ApplicationCommand::Command(RString::from(format!(
+                    "xdg-open {}",
+                    shell_quote(&link)
                 ))),
+                icon: RString::from(a.icon()),
                 score: isize::MAX,
-                name: RString::from(i.title),
-                desc: RSome(RString::from(format!(
-                    "{}",
-                    i.tags
-                        .iter()
-                        .map(|t| format!("#{}", t))
+                name: RString::from(
+                    a.text
+                        .as_ref()
+                        .or(a.comment.as_ref())
+                        .map(|t| t.chars().take(120).collect::<String>())
+                        .unwrap_or_default(),
+                ),
+                desc: RSome(RString::from(
+                    [page, a.comment.clone().filter(|_| a.text.is_some()), parent]
+                        .into_iter()
+                        .flatten()
                         .collect::<Vec<_>>()
-                        .join(" "),
-                ))),
+                        .join(" | "),
+                )),
+            }
+        })
+        .collect()
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Opens `target` through `zotero_open.sh`, which records the access for ranking
+fn open_command(key: &str, target: &str) -> String {
+    format!(
+        "{} {} {}",
+        tilde(OPEN_SCRIPT),
+        shell_quote(key),
+        shell_quote(target)
+    )
+}
+
+fn copy_command(text: &str) -> String {
+    format!(
+        "bash -c {}",
+        shell_quote(&format!(
+            "printf %s {} | xclip -selection clipboard",
+            shell_quote(text)
+        ))
+    )
+}
+
+fn handle_query(query: RStr) -> RVec<FResult> {
+    let query = Query::parse(&query);
+    let now = Utc::now();
+
+    if let Some(library) = Library::current() {
+        let usage = FileUsageCache::default().load_usage();
+        let mut results: Vec<FResult> = search(&library, &query, &usage)
+            .into_iter()
+            .map(|i| {
+                let modified = i.date_modified.map(|d| relative_time(d, now));
+                let tags = modified
+                    .into_iter()
+                    .chain(i.tags.iter().map(|t| format!("#{}", t)))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let file = match query.action {
+                    Action::OpenFile => i
+                        .file_attachment()
+                        .and_then(|a| a.resolve_path(storage_dir()).map(|path| (path, a))),
+                    _ => None,
+                };
+
+                let (cmd, desc, icon) = match (query.action, file) {
+                    (Action::Copy(format), _) => {
+                        let text = format.format(i);
+                        (copy_command(&text), text, i.icon.clone())
+                    }
+                    (Action::OpenFile, Some((path, attachment))) => (
+                        open_command(&i.key, &path.to_string_lossy()),
+                        format!("{} | {}", attachment.title, path.to_string_lossy()),
+                        attachment.icon.clone(),
+                    ),
+                    _ => (open_command(&i.key, &i.select), tags, i.icon.clone()),
+                };
+
+                FResult {
+                    cmd: ApplicationCommand::Command(RString::from(cmd)),
+                    icon: RString::from(icons().resolve(&icon).to_string_lossy().as_ref()),
+                    score: isize::MAX,
+                    name: RString::from(i.title.as_str()),
+                    desc: RSome(RString::from(desc)),
+                }
             })
             .collect();
+
+        if query.action == Action::Select {
+            results.extend(search_annotations(&library, &query));
+        }
+
+        if !library.file_warnings.is_empty() {
+            results.push(FResult {
+                cmd: ApplicationCommand::None,
+                icon: RString::from("dialog-error"),
+                score: isize::MIN,
+                name: RString::from(format!(
+                    "{} zotero library files could not be read",
+                    library.file_warnings.len()
+                )),
+                desc: RSome(RString::from(
+                    library
+                        .file_warnings
+                        .iter()
+                        .map(|w| w.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                )),
+            });
+        }
+
+        let skipped = library.skipped();
+        if !skipped.is_empty() {
+            results.push(FResult {
+                cmd: ApplicationCommand::None,
+                icon: RString::from("dialog-warning"),
+                score: isize::MIN,
+                name: RString::from(format!(
+                    "{} zotero items could not be loaded",
+                    skipped.len()
+                )),
+                desc: RSome(RString::from(
+                    skipped
+                        .iter()
+                        .take(3)
+                        .map(|w| w.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                )),
+            });
+        }
+
+        return RVec::from(results);
     }
 
     RVec::new()
 }
 
 define_plugin!("zotero!", init, handle_query);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_annotations_with_filters() {
+        let library = Library::load(&[PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/My-Library.json"
+        ))])
+        .unwrap();
+        let names = |search: &str| {
+            search_annotations(&library, &Query::parse(search))
+                .into_iter()
+                .map(|r| r.name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let all = names("recurrence");
+        assert!(!all.is_empty());
+        assert_eq!(names("recurrence author:vaswani #ml year:2017"), all);
+        assert!(names("recurrence author:knuth").is_empty());
+        assert!(names("recurrence key:knuth1984").is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    bibtex_loader::{BibTexLoader, LoadWarning, LoaderError, ZoteroAnnotation, ZoteroItem},
+    index::FullTextIndex,
+    query::SortMode,
+};
+
+/// Items of the exported libraries, ready to be searched.
+pub struct Library {
+    /// Most recently modified first
+    pub items: Vec<ZoteroItem>,
+    pub index: FullTextIndex,
+    pub annotations: Vec<ZoteroAnnotation>,
+    pub annotation_index: FullTextIndex,
+    /// Attachment key to the position of its parent in `items`
+    attachment_parents: HashMap<String, usize>,
+    pub warnings: Vec<LoadWarning>,
+    /// Export files that couldn't be read, their items are missing entirely
+    pub file_warnings: Vec<FileWarning>,
+}
+
+/// Export file that couldn't be read or parsed at all.
+#[derive(Debug)]
+pub struct FileWarning {
+    pub file: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for FileWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.file.display(), self.message)
+    }
+}
+
+static CURRENT: RwLock<Option<Arc<Library>>> = RwLock::new(None);
+
+impl Library {
+    /// Loads all export files, a file that can't be read at all becomes a
+    /// [`FileWarning`] unless none of the files could be read.
+    pub fn load(files: &[PathBuf]) -> Result<Library, LoaderError> {
+        let mut items = vec![];
+        let mut annotations = vec![];
+        let mut warnings = vec![];
+        let mut file_warnings = vec![];
+        let mut errors = vec![];
+
+        for file in files {
+            match BibTexLoader::load_zotero(file) {
+                Ok(data) => {
+                    let loaded = BibTexLoader::load_items(data);
+                    items.extend(loaded.items);
+                    annotations.extend(loaded.annotations);
+                    warnings.extend(loaded.warnings);
+                }
+                Err(e) => {
+                    file_warnings.push(FileWarning {
+                        file: file.clone(),
+                        message: e.to_string(),
+                    });
+                    errors.push(e);
+                }
+            }
+        }
+        if errors.len() == files.len() {
+            if let Some(e) = errors.pop() {
+                return Err(e);
+            }
+        }
+        let mut library = Library::new(items, annotations, warnings);
+        library.file_warnings = file_warnings;
+        Ok(library)
+    }
+
+    pub fn new(
+        mut items: Vec<ZoteroItem>,
+        mut annotations: Vec<ZoteroAnnotation>,
+        warnings: Vec<LoadWarning>,
+    ) -> Library {
+        items.sort_by_key(|i| Reverse(i.date_modified));
+        annotations.sort_by_key(|a| Reverse(a.date_modified));
+
+        let attachment_parents = items
+            .iter()
+            .enumerate()
+            .flat_map(|(id, item)| {
+                item.attachments
+                    .iter()
+                    .filter_map(move |a| Some((a.key.clone()?, id)))
+            })
+            .collect();
+
+        Library {
+            index: FullTextIndex::new(&items),
+            annotation_index: FullTextIndex::for_annotations(&annotations),
+            items,
+            annotations,
+            attachment_parents,
+            warnings,
+            file_warnings: vec![],
+        }
+    }
+
+    /// Positions of `items` in the given order
+    pub fn ordered_ids(&self, sort: SortMode) -> Vec<usize> {
+        let mut ids: Vec<usize> = (0..self.items.len()).collect();
+        match sort {
+            // `items` are already sorted by modification date
+            SortMode::Modified => {}
+            SortMode::Added => ids.sort_by_key(|id| Reverse(self.items[*id].date_added)),
+            SortMode::Year => ids.sort_by_key(|id| Reverse(self.items[*id].year)),
+        }
+        ids
+    }
+
+    /// Item the annotated attachment belongs to
+    pub fn annotation_parent(&self, annotation: &ZoteroAnnotation) -> Option<&ZoteroItem> {
+        let id = self
+            .attachment_parents
+            .get(annotation.attachment_key.as_ref()?)?;
+        self.items.get(*id)
+    }
+
+    pub fn skipped(&self) -> Vec<&LoadWarning> {
+        self.warnings.iter().filter(|w| w.skipped).collect()
+    }
+
+    /// Last library loaded by [`reload`], `None` until the first load finished
+    pub fn current() -> Option<Arc<Library>> {
+        CURRENT.read().unwrap().clone()
+    }
+}
+
+/// Parses the export files and swaps the current library.
+///
+/// The previous library is kept when parsing fails.
+pub fn reload(files: &[PathBuf]) {
+    match Library::load(files) {
+        Ok(library) => *CURRENT.write().unwrap() = Some(Arc::new(library)),
+        Err(e) => eprintln!("Failed to load zotero library: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dates::parse_datetime;
+
+    #[test]
+    fn test_load_library() {
+        let fixture = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/My-Library.json"
+        ));
+        let library = Library::load(&[fixture.clone(), fixture]).unwrap();
+
+        assert_eq!(library.items.len(), 4);
+        assert_eq!(library.items[0].key, "ABCD1234");
+        assert_eq!(library.index.search(&["glue"]).len(), 2);
+        assert!(library.warnings.is_empty());
+        // the note was modified after the book
+        assert_eq!(
+            library.items[2].date_modified,
+            parse_datetime("2023-05-02T10:00:00Z")
+        );
+
+        let ids = library.annotation_index.search(&["recurrence"]);
+        assert_eq!(ids.len(), 2);
+        for id in ids {
+            let parent = library.annotation_parent(&library.annotations[id]).unwrap();
+            assert_eq!(parent.key, "ABCD1234");
+        }
+    }
+
+    #[test]
+    fn test_ordered_ids() {
+        let item = |key: &str, year: i32, added: &str, modified: &str| ZoteroItem {
+            key: key.into(),
+            year: Some(year),
+            date_added: parse_datetime(added),
+            date_modified: parse_datetime(modified),
+            ..Default::default()
+        };
+        let library = Library::new(
+            vec![
+                item("A", 2001, "2024-01-01T00:00:00Z", "2024-03-01T00:00:00Z"),
+                item("B", 2020, "2024-02-01T00:00:00Z", "2024-02-01T00:00:00Z"),
+                item("C", 1999, "2024-03-01T00:00:00Z", "2024-01-01T00:00:00Z"),
+            ],
+            vec![],
+            vec![],
+        );
+        let keys = |sort: SortMode| {
+            library
+                .ordered_ids(sort)
+                .into_iter()
+                .map(|id| library.items[id].key.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(keys(SortMode::Modified), vec!["A", "B", "C"]);
+        assert_eq!(keys(SortMode::Added), vec!["C", "B", "A"]);
+        assert_eq!(keys(SortMode::Year), vec!["B", "A", "C"]);
+    }
+
+    #[test]
+    fn test_load_library_with_missing_file() {
+        let fixture = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/My-Library.json"
+        ));
+        let missing = PathBuf::from("/nonexistent/My-Library.json");
+
+        let library = Library::load(&[fixture, missing.clone()]).unwrap();
+        assert_eq!(library.items.len(), 2);
+        assert!(library.skipped().is_empty());
+        assert_eq!(library.file_warnings.len(), 1);
+        assert_eq!(library.file_warnings[0].file, missing);
+        assert!(library.file_warnings[0]
+            .to_string()
+            .starts_with("/nonexistent/My-Library.json: "));
+
+        assert!(Library::load(&[missing]).is_err());
+    }
+}
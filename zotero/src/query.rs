@@ -0,0 +1,307 @@
+use crate::{bibtex_loader::ZoteroItem, citation::CitationFormat};
+
+#[derive(Debug, PartialEq, Clone)]
+enum Term {
+    Title(String),
+    Tag(String),
+    Author(String),
+    Year(i32, i32),
+    Publication(String),
+    Key(String),
+    /// Matched against the [`FullTextIndex`](crate::index::FullTextIndex)
+    FullText(String),
+}
+
+/// Parsed zotero query.
+///
+/// Words are matched against the title; `#tag`, `author:`, `year:2019`,
+/// `year:2015..2020`, `pub:` and `key:` narrow the results further, `full:`
+/// searches abstracts, notes and the extra field.
+/// `copy:key|pandoc|apa|chicago|bibtex` copies the item instead of opening it
+/// and `open:file` opens its PDF/EPUB attachment. `sort:added|modified|year`
+/// changes the order of the results.
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    terms: Vec<Term>,
+    pub action: Action,
+    pub sort: SortMode,
+}
+
+/// Order of the results, most recent first.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum SortMode {
+    #[default]
+    Modified,
+    Added,
+    Year,
+}
+
+impl SortMode {
+    fn parse(keyword: &str) -> Option<SortMode> {
+        match keyword.strip_prefix("sort:")? {
+            "modified" => Some(SortMode::Modified),
+            "added" => Some(SortMode::Added),
+            "year" => Some(SortMode::Year),
+            _ => None,
+        }
+    }
+}
+
+/// What happens when a result is chosen.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Action {
+    /// Focus the item in zotero
+    #[default]
+    Select,
+    Copy(CitationFormat),
+    /// Open the first PDF/EPUB attachment in the system viewer
+    OpenFile,
+}
+
+impl Action {
+    fn parse(keyword: &str) -> Option<Action> {
+        if let Some(format) = keyword.strip_prefix("copy:") {
+            CitationFormat::parse(format).map(Action::Copy)
+        } else if keyword == "open:file" {
+            Some(Action::OpenFile)
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_year_range(value: &str) -> Option<(i32, i32)> {
+    if let Some((from, to)) = value.split_once("..") {
+        let from = if from.is_empty() {
+            i32::MIN
+        } else {
+            from.parse().ok()?
+        };
+        let to = if to.is_empty() {
+            i32::MAX
+        } else {
+            to.parse().ok()?
+        };
+        Some((from, to))
+    } else {
+        let year = value.parse().ok()?;
+        Some((year, year))
+    }
+}
+
+impl Term {
+    fn parse(keyword: &str) -> Term {
+        if let Some(tag) = keyword.strip_prefix('#').filter(|t| !t.is_empty()) {
+            return Term::Tag(tag.to_string());
+        }
+
+        if let Some((field, value)) = keyword.split_once(':').filter(|(_, v)| !v.is_empty()) {
+            match field {
+                "author" => return Term::Author(value.to_string()),
+                "pub" => return Term::Publication(value.to_string()),
+                "key" => return Term::Key(value.to_string()),
+                "full" => return Term::FullText(value.to_string()),
+                "year" => {
+                    if let Some((from, to)) = parse_year_range(value) {
+                        return Term::Year(from, to);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Term::Title(keyword.to_string())
+    }
+
+    fn matches(&self, item: &ZoteroItem) -> bool {
+        match self {
+            Term::Title(keyword) => item.title.to_lowercase().contains(keyword),
+            Term::Tag(tag) => item.tags.iter().any(|t| t.to_lowercase().contains(tag)),
+            Term::Author(author) => item.creators.iter().any(|c| {
+                c.last_name.to_lowercase().contains(author)
+                    || c.first_name
+                        .as_ref()
+                        .is_some_and(|f| f.to_lowercase().contains(author))
+            }),
+            Term::Year(from, to) => item.year.is_some_and(|y| (*from..=*to).contains(&y)),
+            Term::Publication(publication) => item
+                .publication
+                .as_ref()
+                .is_some_and(|p| p.to_lowercase().contains(publication)),
+            Term::Key(key) => item
+                .citation_key
+                .as_ref()
+                .is_some_and(|k| k.to_lowercase().contains(key)),
+            Term::FullText(_) => true,
+        }
+    }
+}
+
+impl Query {
+    pub fn parse(search: &str) -> Query {
+        let mut query = Query {
+            terms: vec![],
+            action: Action::default(),
+            sort: SortMode::default(),
+        };
+
+        for keyword in search.to_lowercase().split_whitespace() {
+            if let Some(action) = Action::parse(keyword) {
+                query.action = action;
+            } else if let Some(sort) = SortMode::parse(keyword) {
+                query.sort = sort;
+            } else {
+                query.terms.push(Term::parse(keyword));
+            }
+        }
+
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.action != Action::OpenFile
+    }
+
+    pub fn matches(&self, item: &ZoteroItem) -> bool {
+        if self.action == Action::OpenFile && item.file_attachment().is_none() {
+            return false;
+        }
+        self.terms.iter().all(|t| t.matches(item))
+    }
+
+    /// Whether `item` passes the `#tag`, `author:`, `year:`, `pub:` and `key:`
+    /// filters, title and full text words are ignored
+    pub fn matches_filters(&self, item: &ZoteroItem) -> bool {
+        self.filters().all(|t| t.matches(item))
+    }
+
+    pub fn has_filters(&self) -> bool {
+        self.filters().next().is_some()
+    }
+
+    fn filters(&self) -> impl Iterator<Item = &Term> {
+        self.terms
+            .iter()
+            .filter(|t| !matches!(t, Term::Title(_) | Term::FullText(_)))
+    }
+
+    pub fn full_text_words(&self) -> Vec<&str> {
+        self.terms
+            .iter()
+            .filter_map(|t| match t {
+                Term::FullText(word) => Some(word.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Title and full text words, annotations are searched by these
+    pub fn text_words(&self) -> Vec<&str> {
+        self.terms
+            .iter()
+            .filter_map(|t| match t {
+                Term::Title(word) | Term::FullText(word) => Some(word.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Same query with title words searched in the body text instead,
+    /// used when nothing matches by title.
+    pub fn full_text_fallback(&self) -> Option<Query> {
+        if !self.terms.iter().any(|t| matches!(t, Term::Title(_))) {
+            return None;
+        }
+
+        Some(Query {
+            terms: self
+                .terms
+                .iter()
+                .map(|t| match t {
+                    Term::Title(word) => Term::FullText(word.clone()),
+                    t => t.clone(),
+                })
+                .collect(),
+            action: self.action,
+            sort: self.sort,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            Query::parse("Art author:Knuth #TeX year:2015..2020 pub:nature key:knuth1984"),
+            Query {
+                terms: vec![
+                    Term::Title("art".into()),
+                    Term::Author("knuth".into()),
+                    Term::Tag("tex".into()),
+                    Term::Year(2015, 2020),
+                    Term::Publication("nature".into()),
+                    Term::Key("knuth1984".into()),
+                ],
+                action: Action::Select,
+                sort: SortMode::Modified,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_action() {
+        let query = Query::parse("copy:bibtex attention");
+        assert_eq!(query.action, Action::Copy(CitationFormat::Bibtex));
+        assert_eq!(query.terms, vec![Term::Title("attention".into())]);
+        assert_eq!(Query::parse("open:file").action, Action::OpenFile);
+        assert_eq!(Query::parse("copy:unknown").action, Action::Select);
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(Query::parse("sort:added").sort, SortMode::Added);
+        assert_eq!(Query::parse("sort:year knuth").sort, SortMode::Year);
+        assert_eq!(Query::parse("knuth").sort, SortMode::Modified);
+        assert_eq!(
+            Query::parse("sort:title").terms,
+            vec![Term::Title("sort:title".into())]
+        );
+    }
+
+    #[test]
+    fn test_full_text_fallback() {
+        let query = Query::parse("attention #ml full:transformer");
+        assert_eq!(query.full_text_words(), vec!["transformer"]);
+        assert_eq!(query.text_words(), vec!["attention", "transformer"]);
+        assert_eq!(
+            query.full_text_fallback().unwrap().full_text_words(),
+            vec!["attention", "transformer"]
+        );
+        assert_eq!(Query::parse("#ml").full_text_fallback(), None);
+    }
+
+    #[test]
+    fn test_matches_filters() {
+        let item = ZoteroItem {
+            title: "Attention is all you need".into(),
+            tags: vec!["ml".into()],
+            year: Some(2017),
+            ..Default::default()
+        };
+        assert!(Query::parse("recurrence #ml year:2017").matches_filters(&item));
+        assert!(!Query::parse("recurrence #ml year:2017").matches(&item));
+        assert!(!Query::parse("attention year:2020").matches_filters(&item));
+        assert!(!Query::parse("attention").has_filters());
+    }
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(Term::parse("year:2019"), Term::Year(2019, 2019));
+        assert_eq!(Term::parse("year:..2000"), Term::Year(i32::MIN, 2000));
+        assert_eq!(Term::parse("year:abc"), Term::Title("year:abc".into()));
+        assert_eq!(Term::parse("author:"), Term::Title("author:".into()));
+    }
+}
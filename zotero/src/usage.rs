@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use shellexpand::tilde;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum UsageError {
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("I/O error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// How often and how recently an item was opened
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ItemUsage {
+    pub key: String,
+    pub count: u32,
+    pub last_accessed_time: u64,
+}
+
+impl ItemUsage {
+    /// Open count weighted by how long ago the item was last opened
+    pub fn frecency(&self, now: u64) -> u32 {
+        let days = now.saturating_sub(self.last_accessed_time) / (24 * 60 * 60);
+        let weight = match days {
+            0..4 => 100,
+            4..14 => 70,
+            14..31 => 50,
+            31..90 => 30,
+            _ => 10,
+        };
+        self.count * weight
+    }
+}
+
+/// Usage of zotero items, collected from the access log `zotero_open.sh` writes.
+pub struct FileUsageCache {
+    file_name: PathBuf,
+    access_log: PathBuf,
+}
+
+impl FileUsageCache {
+    pub fn default() -> Self {
+        Self::new(
+            &*tilde("~/.cache/illef-findex-plugin/zotero.score.cache.json"),
+            &*tilde("~/.cache/illef-findex-plugin/zotero.access_log"),
+        )
+    }
+
+    pub fn new<P: AsRef<Path>, L: AsRef<Path>>(file_name: P, access_log: L) -> Self {
+        FileUsageCache {
+            file_name: file_name.as_ref().to_path_buf(),
+            access_log: access_log.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Adds the entries of the access log to the cache and removes the log
+    pub fn update_usage(&self) -> Result<(), UsageError> {
+        let file_contents = match std::fs::read_to_string(&self.access_log) {
+            Ok(file_contents) => file_contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut usage = self.load_usage();
+
+        for line in file_contents.lines() {
+            if let Some((key, access_time)) = line.split_once('|') {
+                if let Ok(access_time) = access_time.trim().parse::<u64>() {
+                    let key = key.trim().to_string();
+                    let item_usage = usage.entry(key.clone()).or_insert(ItemUsage {
+                        key,
+                        count: 0,
+                        last_accessed_time: access_time,
+                    });
+                    item_usage.count += 1;
+                    item_usage.last_accessed_time = item_usage.last_accessed_time.max(access_time);
+                }
+            }
+        }
+
+        let json_str = serde_json::to_string(&usage.into_values().collect::<Vec<_>>())?;
+        std::fs::write(&self.file_name, json_str)?;
+        std::fs::remove_file(&self.access_log)?;
+
+        Ok(())
+    }
+
+    pub fn load_usage(&self) -> HashMap<String, ItemUsage> {
+        std::fs::read_to_string(&self.file_name)
+            .ok()
+            .and_then(|file_contents| serde_json::from_str::<Vec<ItemUsage>>(&file_contents).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|u| (u.key.clone(), u))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let access_log = dir.path().join("zotero.access_log");
+        let cache = FileUsageCache::new(dir.path().join("zotero.score.cache.json"), &access_log);
+
+        std::fs::write(
+            &access_log,
+            "ABCD1234 | 100\nIJKL9012 | 200\nABCD1234 | 300\n",
+        )
+        .unwrap();
+        cache.update_usage().unwrap();
+        std::fs::write(&access_log, "ABCD1234 | 400\ninvalid line\n").unwrap();
+        cache.update_usage().unwrap();
+
+        let usage = cache.load_usage();
+        assert!(!access_log.exists());
+        assert_eq!(usage["ABCD1234"].count, 3);
+        assert_eq!(usage["ABCD1234"].last_accessed_time, 400);
+        assert_eq!(usage["IJKL9012"].count, 1);
+    }
+
+    #[test]
+    fn test_frecency() {
+        let day = 24 * 60 * 60;
+        let usage = ItemUsage {
+            key: "ABCD1234".into(),
+            count: 3,
+            last_accessed_time: 100 * day,
+        };
+
+        assert_eq!(usage.frecency(101 * day), 300);
+        assert_eq!(usage.frecency(120 * day), 150);
+        assert_eq!(usage.frecency(300 * day), 30);
+    }
+}
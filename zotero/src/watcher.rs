@@ -0,0 +1,116 @@
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+fn is_relevant(event: &notify::Result<Event>, files: &[PathBuf]) -> bool {
+    match event {
+        Ok(event) => {
+            !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|p| files.contains(p))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Calls `on_change` whenever one of the files is written.
+///
+/// Better BibTeX rewrites the export several times in a row, `on_change` runs
+/// once the files were quiet for `debounce`. The parent directories are watched
+/// so files replaced by a rename are picked up as well, files in a missing
+/// directory aren't watched. Blocks until the watcher stops.
+pub fn watch<F: FnMut()>(
+    files: &[PathBuf],
+    debounce: Duration,
+    mut on_change: F,
+) -> notify::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+
+    let dirs: HashSet<&Path> = files.iter().filter_map(|f| f.parent()).collect();
+    for dir in dirs {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            eprintln!("Failed to watch {}: {}", dir.display(), e);
+        }
+    }
+
+    while let Ok(event) = rx.recv() {
+        if !is_relevant(&event, files) {
+            continue;
+        }
+
+        // other files in the directories don't extend the quiet period
+        let mut quiet_until = Instant::now() + debounce;
+        loop {
+            match rx.recv_timeout(quiet_until.saturating_duration_since(Instant::now())) {
+                Ok(event) if is_relevant(&event, files) => quiet_until = Instant::now() + debounce,
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+        on_change();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_watch_debounces_rewrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("My-Library.json");
+        std::fs::write(&file, "{}").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let files = vec![file.clone()];
+        thread::spawn(move || {
+            watch(&files, Duration::from_millis(300), || tx.send(()).unwrap()).unwrap();
+        });
+        thread::sleep(Duration::from_millis(200));
+
+        std::fs::write(dir.path().join("unrelated.json"), "{}").unwrap();
+        for _ in 0..5 {
+            std::fs::write(&file, "{}").unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn test_watch_skips_missing_dirs_and_unrelated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("My-Library.json");
+        std::fs::write(&file, "{}").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let files = vec![PathBuf::from("/nonexistent/My-Library.json"), file.clone()];
+        thread::spawn(move || {
+            watch(&files, Duration::from_millis(300), || tx.send(()).unwrap()).unwrap();
+        });
+        thread::sleep(Duration::from_millis(200));
+
+        std::fs::write(&file, "{}").unwrap();
+        let unrelated = dir.path().join("unrelated.json");
+        let start = Instant::now();
+        let changed = loop {
+            std::fs::write(&unrelated, "{}").unwrap();
+            if rx.recv_timeout(Duration::from_millis(50)).is_ok() {
+                break true;
+            }
+            if start.elapsed() > Duration::from_secs(3) {
+                break false;
+            }
+        };
+        assert!(changed);
+    }
+}